@@ -1,8 +1,17 @@
 use crate::WithStatus::*;
+use near_contract_standards::non_fungible_token::metadata::{
+    NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
+};
+use near_contract_standards::non_fungible_token::{NonFungibleToken, Token, TokenId};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::json_types::{Base64VecU8, U128};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, AccountId};
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near_bindgen, AccountId, Balance, Gas, Promise,
+    PromiseOrValue, PromiseResult,
+};
+use std::collections::HashMap;
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(crate = "near_sdk::serde", tag = "status")]
@@ -20,9 +29,19 @@ pub enum WithStatus<T> {
     Empty,
 }
 
-impl<T> WithStatus<T> {
+// 可以被移除的內容（記錄移除時間與移除者，供復原使用）
+pub trait Removable {
+    fn removed_at_mut(&mut self) -> &mut Option<u64>;
+    fn removed_by_mut(&mut self) -> &mut Option<AccountId>;
+}
+
+impl<T: Removable> WithStatus<T> {
     // 把請求的String格式參數轉換成Enum
-    fn new_with_status_string(obj: T, str: String) -> WithStatus<T> {
+    fn new_with_status_string(mut obj: T, str: String) -> WithStatus<T> {
+        // 移除時記錄移除時間與移除者，其他狀態則清除
+        let removed = str == "Removed";
+        *obj.removed_at_mut() = Some(env::block_timestamp()).filter(|_| removed);
+        *obj.removed_by_mut() = Some(env::signer_account_id()).filter(|_| removed);
         if str == "Open" {
             Open(obj)
         } else if str == "Locked" {
@@ -33,6 +52,517 @@ impl<T> WithStatus<T> {
             Empty
         }
     }
+
+    // 把狀態換成指定的狀態（無論原本狀態，Empty維持不變）
+    fn set_status(&mut self, status: &str) {
+        if let Some(obj) = std::mem::replace(self, Empty).into_inner() {
+            *self = WithStatus::new_with_status_string(obj, status.to_string());
+        }
+    }
+}
+
+impl<T> WithStatus<T> {
+    // 取出內部資料（無論狀態）
+    fn into_inner(self) -> Option<T> {
+        match self {
+            Open(obj) | Locked(obj) | Removed(obj) => Some(obj),
+            Empty => None,
+        }
+    }
+
+    // 取得內部資料（無論狀態）
+    fn inner(&self) -> Option<&T> {
+        match self {
+            Open(obj) | Locked(obj) | Removed(obj) => Some(obj),
+            Empty => None,
+        }
+    }
+
+    // 取得內部資料的可變參考（無論狀態）
+    fn inner_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Open(obj) | Locked(obj) | Removed(obj) => Some(obj),
+            Empty => None,
+        }
+    }
+
+    // 狀態的字串格式（與new_with_status_string相反，Empty沒有對應的字串）
+    fn status_string(&self) -> Option<&'static str> {
+        match self {
+            Open(_) => Some("Open"),
+            Locked(_) => Some("Locked"),
+            Removed(_) => Some("Removed"),
+            Empty => None,
+        }
+    }
+
+    // 轉換內部資料，狀態維持不變
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> WithStatus<U> {
+        match self {
+            Open(obj) => Open(f(obj)),
+            Locked(obj) => Locked(f(obj)),
+            Removed(obj) => Removed(f(obj)),
+            Empty => Empty,
+        }
+    }
+}
+
+// 淨推文數超過這個數字就會「爆」
+const EXPLOSIVE_SCORE: i64 = 100;
+// 舊版合約沒有記錄的時間（發文、移除時間），鏈上的區塊時間不會是0
+const UNKNOWN_TIMESTAMP: u64 = 0;
+// 打賞/提領轉帳完成後的callback所需的gas
+const TIP_CALLBACK_GAS: Gas = Gas(10_000_000_000_000);
+// 呼叫代幣合約ft_transfer所需的gas
+const FT_TRANSFER_GAS: Gas = Gas(5_000_000_000_000);
+// 聲望紀錄的時間區間（1天，同一天內的變動只保留最後的聲望）
+const KARMA_CHECKPOINT_INTERVAL: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+// NEP-141代幣合約
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+}
+
+// NEP-171 NFT合約（NEP-181列舉介面）
+#[ext_contract(ext_nft)]
+pub trait NftEnumeration {
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+// 操作對象（文章或留言）
+pub enum ContentTarget {
+    Post { post_id: u128 },
+    Comment { post_id: u128, comment_id: u128 },
+}
+
+impl ContentTarget {
+    // 對象所在的文章
+    fn post_id(&self) -> u128 {
+        match self {
+            ContentTarget::Post { post_id } | ContentTarget::Comment { post_id, .. } => *post_id,
+        }
+    }
+
+    // 對象所在的留言（對象是文章就是None）
+    fn comment_id(&self) -> Option<u128> {
+        match self {
+            ContentTarget::Post { .. } => None,
+            ContentTarget::Comment { comment_id, .. } => Some(*comment_id),
+        }
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 檢舉
+pub struct Report {
+    // 檢舉者
+    reporter: AccountId,
+    // 原因
+    reason: String,
+    // 檢舉時間
+    reported_at: u64,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 待處理的檢舉案件（同一個對象的檢舉合併成一件）
+pub struct ReportCase {
+    // 被檢舉的對象
+    target: ContentTarget,
+    // 檢舉（每個帳號只算一次）
+    reports: Vec<Report>,
+    // 是否因為檢舉數過多而被自動隱藏
+    auto_hidden: bool,
+    // 自動隱藏前的狀態（駁回時恢復成這個狀態）
+    status_before_hide: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 管理員處理檢舉的方式
+pub enum ModerationAction {
+    // 駁回（被自動隱藏的對象會恢復成隱藏前的狀態）
+    Dismiss,
+    // 鎖定
+    Lock,
+    // 移除
+    Remove,
+}
+
+// 錯誤（會讓交易失敗並回傳錯誤訊息）
+pub enum BoardError {
+    // 帳號被封鎖
+    Banned {
+        board: Option<String>,
+        reason: String,
+        expires_at: Option<u64>,
+    },
+    // 超過發文/留言頻率限制，retry_after之後才能再試
+    RateLimited {
+        action: &'static str,
+        retry_after: u64,
+    },
+    // 欄位不能是空的
+    EmptyField {
+        field: &'static str,
+    },
+    // 欄位超過長度上限
+    TooLong {
+        field: &'static str,
+        max: u32,
+        unit: &'static str,
+    },
+    // 標籤不合法（只接受文字、數字、「-」與「_」）
+    InvalidTag {
+        tag: String,
+    },
+    // 標籤數量超過上限
+    TooManyTags {
+        max: u32,
+    },
+    // 文章的留言數量已經達到上限
+    TooManyComments {
+        max: u32,
+    },
+    // 回覆超過最大層數
+    ReplyTooDeep {
+        max: u32,
+    },
+    // 無法打賞
+    CannotTip {
+        reason: &'static str,
+    },
+    // 懸賞不合法或無法結算
+    InvalidBounty {
+        reason: &'static str,
+    },
+    // 投票設定不合法
+    InvalidPoll {
+        reason: &'static str,
+    },
+    // 看板需要持有指定的NFT或代幣，而且要先呼叫verify_membership確認資格
+    MembershipRequired {
+        board: String,
+    },
+    // 看板不存在或沒有持有資格的限制，不需要確認資格
+    NotGated {
+        board: String,
+    },
+    // 引用的文章或留言不存在或已被移除
+    InvalidReference {
+        post_id: u128,
+        comment_id: Option<u128>,
+    },
+    // 引用數量超過上限
+    TooManyReferences {
+        max: u32,
+    },
+    // 數量已經達到上限（追蹤、收藏等）
+    LimitReached {
+        what: &'static str,
+        max: u32,
+    },
+    // 付費文章設定不合法
+    InvalidPremium {
+        reason: &'static str,
+    },
+    // 無法購買付費文章
+    CannotPurchase {
+        reason: &'static str,
+    },
+    // 附加的押金不足
+    InsufficientDeposit {
+        required: Balance,
+    },
+    // 提領金額不合法（是0或超過可以提領的金額）
+    InvalidWithdrawal {
+        amount: Balance,
+        available: Balance,
+    },
+    // 無法把文章鑄造成NFT
+    CannotMint {
+        reason: &'static str,
+    },
+    // 無法計算NFT的分潤
+    CannotPayout {
+        reason: &'static str,
+    },
+    // 無法寄站內信
+    CannotMessage {
+        reason: &'static str,
+    },
+    // 僅限合約擁有者的操作
+    OwnerOnly {
+        action: &'static str,
+    },
+}
+
+impl std::fmt::Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::Banned {
+                board,
+                reason,
+                expires_at,
+            } => {
+                match board {
+                    Some(board) => write!(f, "Account is banned from board {}", board)?,
+                    None => write!(f, "Account is banned")?,
+                }
+                write!(f, ": {}", reason)?;
+                match expires_at {
+                    Some(expires_at) => write!(f, " (until block timestamp {})", expires_at),
+                    None => write!(f, " (permanently)"),
+                }
+            }
+            BoardError::RateLimited {
+                action,
+                retry_after,
+            } => write!(
+                f,
+                "Too many {}s, try again after block timestamp {}",
+                action, retry_after
+            ),
+            BoardError::EmptyField { field } => write!(f, "The {} must not be empty", field),
+            BoardError::TooLong { field, max, unit } => {
+                write!(f, "The {} must be at most {} {}", field, max, unit)
+            }
+            BoardError::InvalidTag { tag } => write!(
+                f,
+                "Invalid tag \"{}\": only letters, digits, \"-\" and \"_\" are allowed",
+                tag
+            ),
+            BoardError::TooManyTags { max } => write!(f, "A post can have at most {} tags", max),
+            BoardError::TooManyComments { max } => {
+                write!(f, "A post can have at most {} comments", max)
+            }
+            BoardError::ReplyTooDeep { max } => {
+                write!(f, "Replies can be nested at most {} levels deep", max)
+            }
+            BoardError::CannotTip { reason } => write!(f, "Cannot tip: {}", reason),
+            BoardError::InvalidBounty { reason } => write!(f, "Invalid bounty: {}", reason),
+            BoardError::InvalidPoll { reason } => write!(f, "Invalid poll: {}", reason),
+            BoardError::MembershipRequired { board } => write!(
+                f,
+                "Board {} is only open to token holders, call verify_membership first",
+                board
+            ),
+            BoardError::NotGated { board } => {
+                write!(f, "Board {} does not exist or is not token-gated", board)
+            }
+            BoardError::InvalidReference {
+                post_id,
+                comment_id,
+            } => {
+                write!(f, "Cannot reference post #{}", post_id)?;
+                if let Some(comment_id) = comment_id {
+                    write!(f, " comment #{}", comment_id)?;
+                }
+                write!(f, ": it does not exist or has been removed")
+            }
+            BoardError::TooManyReferences { max } => {
+                write!(f, "At most {} references are allowed", max)
+            }
+            BoardError::LimitReached { what, max } => {
+                write!(f, "At most {} {} are allowed", max, what)
+            }
+            BoardError::InvalidPremium { reason } => {
+                write!(f, "Invalid premium post: {}", reason)
+            }
+            BoardError::CannotPurchase { reason } => write!(f, "Cannot purchase: {}", reason),
+            BoardError::InsufficientDeposit { required } => write!(
+                f,
+                "Attached deposit must be at least {} yoctoNEAR",
+                required
+            ),
+            BoardError::InvalidWithdrawal { amount, available } => write!(
+                f,
+                "Cannot withdraw {}, the available amount is {}",
+                amount, available
+            ),
+            BoardError::CannotMint { reason } => write!(f, "Cannot mint: {}", reason),
+            BoardError::CannotPayout { reason } => write!(f, "Cannot payout: {}", reason),
+            BoardError::CannotMessage { reason } => {
+                write!(f, "Cannot send the message: {}", reason)
+            }
+            BoardError::OwnerOnly { action } => {
+                write!(f, "Only the contract owner can {}", action)
+            }
+        }
+    }
+}
+
+impl BoardError {
+    // 中止這次呼叫並回傳錯誤訊息
+    fn panic(&self) -> ! {
+        env::panic_str(&self.to_string())
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 封鎖
+pub struct Ban {
+    // 被封鎖的帳號
+    account_id: AccountId,
+    // 看板（null代表全站封鎖）
+    board: Option<String>,
+    // 原因
+    reason: String,
+    // 到期時間（null代表永久封鎖）
+    expires_at: Option<u64>,
+    // 執行封鎖的管理員
+    banned_by: AccountId,
+    // 封鎖時間
+    banned_at: u64,
+}
+
+impl Ban {
+    // 是否還在封鎖期間
+    fn is_active(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at > env::block_timestamp(),
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+// 推文
+pub enum Reaction {
+    // 推
+    Push,
+    // 噓
+    Boo,
+    // →
+    Neutral,
+}
+
+#[derive(Clone, Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 推文統計
+pub struct ReactionTally {
+    // 推
+    push: u64,
+    // 噓
+    boo: u64,
+    // →
+    neutral: u64,
+}
+
+impl ReactionTally {
+    // 取得對應推文種類的計數
+    fn count_mut(&mut self, reaction: Reaction) -> &mut u64 {
+        match reaction {
+            Reaction::Push => &mut self.push,
+            Reaction::Boo => &mut self.boo,
+            Reaction::Neutral => &mut self.neutral,
+        }
+    }
+
+    // 淨推文數（推 - 噓）
+    fn net_score(&self) -> i64 {
+        self.push as i64 - self.boo as i64
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde", default)]
+// 發文的選填設定（沒有給的欄位都是null）
+pub struct PostOptions {
+    // 所屬看板
+    board: Option<String>,
+    // 發文多久後自動鎖定（奈秒，null代表使用看板的設定）
+    auto_lock_after: Option<u64>,
+    // 引用的文章或留言
+    references: Option<Vec<ContentTarget>>,
+    // 懸賞截止時間（有指定的話是懸賞文章，附加的押金就是懸賞金額）
+    bounty_deadline: Option<u64>,
+    // 附加的投票
+    poll: Option<NewPoll>,
+    // 付費文章的價格（yoctoNEAR，有指定的話content必須是在鏈下加密過的內文）
+    premium_price: Option<U128>,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 懸賞
+pub struct Bounty {
+    // 懸賞金額（yoctoNEAR）
+    amount: U128,
+    // 截止時間，之後管理員可以選出最佳解答
+    // 再經過bounty_grace_period還沒選出的話，任何人都可以把懸賞退還給作者
+    deadline: u64,
+    // 最佳解答的留言（還沒選出就是null）
+    accepted_comment_id: Option<u128>,
+    // 懸賞是否已經發放或退還
+    settled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// NFT售出時各帳號應得的金額（NEP-199）
+pub struct Payout {
+    payout: HashMap<AccountId, U128>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 發文時附加的投票設定
+pub struct NewPoll {
+    // 選項
+    options: Vec<String>,
+    // 是否可以複選
+    multiple_choice: bool,
+    // 結束時間
+    ends_at: u64,
+    // 是否公開投票者
+    voters_visible: bool,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 投票
+pub struct Poll {
+    // 選項
+    options: Vec<String>,
+    // 是否可以複選
+    multiple_choice: bool,
+    // 結束時間，之後就不能再投票
+    ends_at: u64,
+    // 是否公開投票者
+    voters_visible: bool,
+    // 各選項的票數
+    counts: Vec<u64>,
+    // 投票者與他選的選項（透過get_poll_results查詢，是否公開由voters_visible決定）
+    #[serde(skip)]
+    votes: Vec<(AccountId, Vec<u32>)>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+// 查詢投票結果時回傳的內容
+pub struct PollResults {
+    // 選項
+    options: Vec<String>,
+    // 各選項的票數
+    counts: Vec<u64>,
+    // 投票人數
+    number_of_voters: u64,
+    // 是否可以複選
+    multiple_choice: bool,
+    // 結束時間
+    ends_at: u64,
+    // 是否已經結束（超過結束時間或文章不是開放狀態）
+    closed: bool,
+    // 投票者與他選的選項（不公開投票者就是null）
+    voters: Option<Vec<(AccountId, Vec<u32>)>>,
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
@@ -52,6 +582,38 @@ pub struct Post {
     creator_user_id: AccountId,
     // 留言
     comments: Vec<WithStatus<Comment>>,
+    // 所屬看板（沒有指定就是null）
+    board: Option<String>,
+    // 移除時間（沒有被移除就是null）
+    removed_at: Option<u64>,
+    // 移除者（沒有被移除就是null）
+    removed_by: Option<AccountId>,
+    // 發文時間
+    created_at: u64,
+    // 發文多久後自動鎖定（奈秒，null代表使用看板的設定）
+    auto_lock_after: Option<u64>,
+    // 最後一次解除鎖定的時間（自動鎖定會從這個時間重新計算）
+    unlocked_at: Option<u64>,
+    // 推文（每個帳號只能有一個）
+    reactions: Vec<(AccountId, Reaction)>,
+    // 推文統計
+    reaction_tally: ReactionTally,
+    // 引用的文章或留言
+    references: Vec<ContentTarget>,
+    // 懸賞（不是懸賞文章就是null）
+    bounty: Option<Bounty>,
+    // 投票（沒有附加投票就是null）
+    poll: Option<Poll>,
+    // 付費文章的價格（yoctoNEAR，內文是在鏈下加密過的，不是付費文章就是null）
+    premium_price: Option<U128>,
+    // 作者顯示名稱（僅在查詢時填入，不存入鏈上）
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator_display_name: Option<String>,
+    // 引用這篇文章的文章與留言（僅在查詢單篇文章時填入，不存入鏈上）
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    referenced_by: Vec<ContentTarget>,
 }
 
 impl Default for Post {
@@ -64,537 +626,5720 @@ impl Default for Post {
             users_who_liked: Vec::default(),
             creator_user_id: env::signer_account_id(),
             comments: Vec::default(),
+            board: None,
+            removed_at: None,
+            removed_by: None,
+            created_at: env::block_timestamp(),
+            auto_lock_after: None,
+            unlocked_at: None,
+            reactions: Vec::default(),
+            reaction_tally: ReactionTally::default(),
+            references: Vec::default(),
+            bounty: None,
+            poll: None,
+            premium_price: None,
+            creator_display_name: None,
+            referenced_by: Vec::default(),
         }
     }
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
-// 留言
+// 留言（回覆文章或其他留言）
 pub struct Comment {
+    // 留言流水號（在文章中不會重複也不會改變，回覆、檢舉、表情符號等都用它來指定留言）
+    id: u128,
+    // 回覆的留言（直接回覆文章就是null）
+    parent_id: Option<u128>,
+    // 層數（直接回覆文章是0）
+    depth: u32,
     // 留言者
     comment_creator_user_id: AccountId,
     // 內容
     content: String,
     // 點讚用戶
     users_who_liked: Vec<AccountId>,
-    // 子留言
-    sub_comments: Vec<WithStatus<SubComment>>,
+    // 移除時間（沒有被移除就是null）
+    removed_at: Option<u64>,
+    // 移除者（沒有被移除就是null）
+    removed_by: Option<AccountId>,
+    // 推文（每個帳號只能有一個）
+    reactions: Vec<(AccountId, Reaction)>,
+    // 推文統計
+    reaction_tally: ReactionTally,
+    // 引用的文章或留言
+    references: Vec<ContentTarget>,
+    // 留言者顯示名稱（僅在查詢時填入，不存入鏈上）
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator_display_name: Option<String>,
+    // 引用這則留言的文章與留言（僅在查詢單篇文章時填入，不存入鏈上）
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    referenced_by: Vec<ContentTarget>,
 }
 
 impl Default for Comment {
     fn default() -> Self {
         Self {
+            id: 0,
+            parent_id: None,
+            depth: 0,
             comment_creator_user_id: env::signer_account_id(),
             content: String::default(),
             users_who_liked: Vec::default(),
-            sub_comments: Vec::default(),
+            removed_at: None,
+            removed_by: None,
+            reactions: Vec::default(),
+            reaction_tally: ReactionTally::default(),
+            references: Vec::default(),
+            creator_display_name: None,
+            referenced_by: Vec::default(),
         }
     }
 }
 
+impl Post {
+    // 透過流水號找出留言（不依賴留言在陣列中的位置）
+    fn comment(&self, comment_id: u128) -> Option<&WithStatus<Comment>> {
+        self.comments.iter().find(|comment_with_status| {
+            comment_with_status
+                .inner()
+                .is_some_and(|comment| comment.id == comment_id)
+        })
+    }
+
+    // 透過流水號找出留言的可變參考
+    fn comment_mut(&mut self, comment_id: u128) -> Option<&mut WithStatus<Comment>> {
+        self.comments.iter_mut().find(|comment_with_status| {
+            comment_with_status
+                .inner()
+                .is_some_and(|comment| comment.id == comment_id)
+        })
+    }
+
+    // 下一則留言的流水號（流水號只會遞增，不會重複使用）
+    fn next_comment_id(&self) -> u128 {
+        self.comments
+            .iter()
+            .filter_map(|comment_with_status| comment_with_status.inner())
+            .map(|comment| comment.id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Removable for Post {
+    fn removed_at_mut(&mut self) -> &mut Option<u64> {
+        &mut self.removed_at
+    }
+
+    fn removed_by_mut(&mut self) -> &mut Option<AccountId> {
+        &mut self.removed_by
+    }
+}
+
+impl Removable for Comment {
+    fn removed_at_mut(&mut self) -> &mut Option<u64> {
+        &mut self.removed_at
+    }
+
+    fn removed_by_mut(&mut self) -> &mut Option<AccountId> {
+        &mut self.removed_by
+    }
+}
+
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
-// 子留言
-pub struct SubComment {
-    // 留言者
-    comment_creator_user_id: AccountId,
-    // 內容
-    content: String,
-    // 點讚用戶
-    users_who_liked: Vec<AccountId>,
+// 通知種類
+pub enum NotificationKind {
+    // 在文章或留言中被提及（@帳號）
+    Mention,
+    // 自己的文章或留言被回覆
+    Reply,
 }
 
-impl Default for SubComment {
-    fn default() -> Self {
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 通知
+pub struct Notification {
+    // 通知流水號（每個帳號各自從1開始）
+    id: u64,
+    // 種類
+    kind: NotificationKind,
+    // 提及或回覆的文章/留言
+    target: ContentTarget,
+    // 觸發通知的帳號
+    from: AccountId,
+    // 通知時間
+    created_at: u64,
+    // 是否已讀（僅在查詢時填入，不存入鏈上）
+    #[borsh_skip]
+    #[serde(default)]
+    read: bool,
+}
+
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+// 帳號的通知收件匣
+pub struct Inbox {
+    // 通知（舊的在前）
+    notifications: Vec<Notification>,
+    // 下一則通知的流水號
+    next_id: u64,
+    // 已讀到第幾則通知
+    read_up_to: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+// 垃圾桶中的內容
+pub struct RemovedItem {
+    // 被移除的對象
+    target: ContentTarget,
+    // 標題（只有文章有，且只有本人可以看到）
+    title: Option<String>,
+    // 內容（只有本人可以看到）
+    content: Option<String>,
+    // 移除時間
+    removed_at: u64,
+    // 移除者（不是本人的話只有管理員可以復原）
+    removed_by: Option<AccountId>,
+    // 可以復原的期限
+    restorable_until: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+// 文章摘要（列表用，不包含內文與留言）
+pub struct PostSummary {
+    // 文章流水號
+    id: u128,
+    // 標題
+    title: String,
+    // 標籤
+    tags: Vec<String>,
+    // 所屬看板
+    board: Option<String>,
+    // 作者
+    creator_user_id: AccountId,
+    // 讚數
+    like_count: u64,
+    // 留言數
+    comment_count: u64,
+    // 推文統計
+    reaction_tally: ReactionTally,
+    // 淨推文數（推 - 噓）
+    net_score: i64,
+    // 標記（淨推文數超過100會顯示「爆」）
+    badge: Option<String>,
+}
+
+impl From<&Post> for PostSummary {
+    fn from(post: &Post) -> Self {
+        let net_score = post.reaction_tally.net_score();
         Self {
-            comment_creator_user_id: env::signer_account_id(),
-            content: String::default(),
-            users_who_liked: Vec::default(),
+            id: post.id,
+            title: post.title.clone(),
+            tags: post.tags.clone(),
+            board: post.board.clone(),
+            creator_user_id: post.creator_user_id.clone(),
+            like_count: post.users_who_liked.len() as u64,
+            comment_count: post
+                .comments
+                .iter()
+                .filter(|comment_with_status| matches!(comment_with_status, Open(_) | Locked(_)))
+                .count() as u64,
+            reaction_tally: post.reaction_tally.clone(),
+            net_score,
+            badge: if net_score > EXPLOSIVE_SCORE {
+                Some("爆".to_string())
+            } else {
+                None
+            },
         }
     }
 }
 
-#[near_bindgen]
-#[derive(BorshSerialize, BorshDeserialize)]
-pub struct BulletinBoard {
-    posts: UnorderedMap<u128, WithStatus<Post>>,
-    tags: UnorderedMap<String, Vec<u128>>,
-    number_of_posts: u128,
-    likes_by_user_id: UnorderedMap<AccountId, Vec<Post>>,
+#[derive(Clone, Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 個人資料
+pub struct Profile {
+    // 顯示名稱
+    display_name: String,
+    // 自我介紹
+    bio: String,
+    // 頭像（網址或IPFS CID）
+    avatar: String,
+    // 簽名檔
+    signature: String,
 }
 
-impl Default for BulletinBoard {
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 站內信（內容由寄件者在鏈下用收件者的公鑰加密）
+pub struct Message {
+    // 站內信流水號
+    id: u64,
+    // 寄件者
+    from: AccountId,
+    // 收件者
+    to: AccountId,
+    // 加密過的內容
+    ciphertext: String,
+    // 加密用的nonce
+    nonce: String,
+    // 寄出時間
+    sent_at: u64,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 收藏的文章
+pub struct Bookmark {
+    // 文章流水號
+    post_id: u128,
+    // 收藏資料夾（null代表未分類）
+    folder: Option<String>,
+    // 收藏時間
+    bookmarked_at: u64,
+    // 文章是否已經被移除（僅在查詢時填入，不存入鏈上）
+    #[borsh_skip]
+    #[serde(default)]
+    removed: bool,
+}
+
+#[derive(Default, BorshSerialize, BorshDeserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+// 帳號追蹤的對象
+pub struct Following {
+    // 追蹤的帳號
+    accounts: Vec<AccountId>,
+    // 追蹤的標籤
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+// 查詢個人資料時回傳的內容
+pub struct ProfileView {
+    // 帳號
+    account_id: AccountId,
+    // 個人資料（沒設定過就是null）
+    profile: Option<Profile>,
+    // 站內信加密用的公鑰（沒公開過就是null）
+    public_key: Option<String>,
+    // 文章數
+    post_count: u64,
+    // 留言數（包含回覆）
+    comment_count: u64,
+    // 文章與留言收到的讚數
+    like_count: u64,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 看板
+pub struct Board {
+    // 看板名稱
+    name: String,
+    // 看板說明
+    description: String,
+    // 是否為限制看板（需要足夠的聲望才能發文）
+    restricted: bool,
+    // 看板中的文章發文多久後自動鎖定（奈秒，null代表不自動鎖定）
+    auto_lock_after: Option<u64>,
+    // 持有指定的NFT或代幣才能在看板發文與留言（null代表不限制）
+    // 鏈上的資料本來就是公開的，合約無法限制讀取，隱藏內容需要由前端處理
+    gate: Option<BoardGate>,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 看板的持有資格
+pub enum BoardGate {
+    // 持有指定合約的NFT（NEP-171，合約需要支援NEP-181的nft_supply_for_owner）
+    Nft {
+        contract_id: AccountId,
+    },
+    // 持有指定數量以上的代幣（NEP-141）
+    Ft {
+        contract_id: AccountId,
+        min_balance: U128,
+    },
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 各項權限所需的聲望
+pub struct KarmaThresholds {
+    // 建立新標籤
+    create_tag: u64,
+    // 在限制看板發文
+    post_in_restricted_board: u64,
+    // 不受發文/留言頻率限制
+    bypass_rate_limit: u64,
+}
+
+impl Default for KarmaThresholds {
     fn default() -> Self {
         Self {
-            posts: UnorderedMap::new(b'm'),
-            tags: UnorderedMap::new(b'n'),
-            number_of_posts: 0,
-            likes_by_user_id: UnorderedMap::new(b'o'),
+            create_tag: 0,
+            post_in_restricted_board: 10,
+            bypass_rate_limit: 100,
         }
     }
 }
 
-#[near_bindgen]
-impl BulletinBoard {
-    // 確認請求的status參數是否正確
-    fn check_status_string(str: &String) -> bool {
-        str == "Open" || str == "Locked" || str == "Removed"
-    }
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 內容長度與數量限制
+pub struct ContentLimits {
+    // 標題最長字數
+    max_title_length: u32,
+    // 內文最大位元組數
+    max_content_bytes: u32,
+    // 每篇文章最多可以有幾個標籤
+    max_tags_per_post: u32,
+    // 標籤最長字數
+    max_tag_length: u32,
+    // 留言最長字數
+    max_comment_length: u32,
+    // 回覆留言最長字數
+    max_reply_length: u32,
+    // 回覆最多可以有幾層
+    max_reply_depth: u32,
+    // 每篇文章最多可以有幾則留言（包含回覆）
+    max_comments_per_post: u32,
+    // 每篇文章或留言最多可以引用幾個對象
+    max_references: u32,
+    // 每篇文章或留言最多通知幾個被提及的帳號（超過的提及會被忽略）
+    max_mentions: u32,
+    // 每個帳號最多保留幾則通知（超過時刪除最舊的通知）
+    max_notifications_per_account: u32,
+    // 投票最多可以有幾個選項
+    max_poll_options: u32,
+    // 投票選項最長字數
+    max_poll_option_length: u32,
+    // 站內信最大位元組數（加密後）
+    max_message_bytes: u32,
+    // 每個帳號最多可以追蹤幾個帳號
+    max_following_accounts: u32,
+    // 每個帳號最多可以追蹤幾個標籤
+    max_following_tags: u32,
+    // 動態中每個作者最多列出幾篇最新的文章
+    max_feed_posts_per_author: u32,
+    // 每個帳號最多可以收藏幾篇文章
+    max_bookmarks: u32,
+    // 收藏資料夾名稱最長字數
+    max_folder_name_length: u32,
+    // 每個帳號最多可以拒收幾個帳號的站內信
+    max_blocked_accounts: u32,
+    // 檢舉理由最長字數
+    max_report_reason_length: u32,
+}
 
-    // 新增文章
-    pub fn add_post(
-        &mut self,
-        title: String,
-        content: String,
-        tags: Vec<String>,
-    ) -> WithStatus<Post> {
-        // 產生新的文章
-        let new_post = Post {
-            id: self.number_of_posts,
-            title,
-            content,
-            tags: tags.clone(),
-            ..Post::default()
-        };
-        // 將新的文章存入
-        self.posts.insert(&new_post.id, &Open(new_post.clone()));
-        // 將請求中的tag存入
-        tags.iter()
-            .for_each(|new_tag| match self.tags.get(new_tag) {
-                // 如果已經有tag存在，將id存入vector中
-                Some(mut posts_id_vec) => posts_id_vec.push(new_post.id),
-                // 如果tag不存在，建立一個新的vector，並將新的tag存入
-                None => {
-                    self.tags.insert(new_tag, &vec![new_post.id]);
-                }
-            });
-        // post總數+1
-        self.number_of_posts += 1;
-        // 回傳
-        Open(new_post)
+impl Default for ContentLimits {
+    fn default() -> Self {
+        Self {
+            max_title_length: 100,
+            max_content_bytes: 10_000,
+            max_tags_per_post: 5,
+            max_tag_length: 32,
+            max_comment_length: 500,
+            max_reply_length: 500,
+            max_reply_depth: 5,
+            max_comments_per_post: 500,
+            max_references: 10,
+            max_mentions: 10,
+            max_notifications_per_account: 100,
+            max_poll_options: 10,
+            max_poll_option_length: 100,
+            max_message_bytes: 10_000,
+            max_following_accounts: 500,
+            max_following_tags: 100,
+            max_feed_posts_per_author: 100,
+            max_bookmarks: 1_000,
+            max_folder_name_length: 32,
+            max_blocked_accounts: 500,
+            max_report_reason_length: 200,
+        }
     }
+}
 
-    // 查詢所有文章
-    pub fn get_all_post(&self) -> Vec<(u128, WithStatus<Post>)> {
-        self.posts
-            .to_vec()
-            .into_iter()
-            // 僅有狀態為開放與鎖定的文章可以被查詢到
-            .filter(|(_, post_with_status)| matches!(post_with_status, Open(_) | Locked(_)))
-            .collect::<Vec<(u128, WithStatus<Post>)>>()
-    }
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 發文/留言頻率限制（時間單位與env::block_timestamp()相同，都是奈秒）
+pub struct RateLimits {
+    // 每段時間內最多可以發幾篇文章
+    max_posts: u32,
+    // 發文的計算區間
+    post_window: u64,
+    // 每段時間內最多可以留幾則留言
+    max_comments: u32,
+    // 留言的計算區間
+    comment_window: u64,
+}
 
-    // 透過文字查詢文章
-    pub fn search_post(&self, q: String) -> Vec<(u128, WithStatus<Post>)> {
-        self.posts
-            .to_vec()
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            // 每小時5篇文章
+            max_posts: 5,
+            post_window: 60 * 60 * 1_000_000_000,
+            // 每分鐘10則留言
+            max_comments: 10,
+            comment_window: 60 * 1_000_000_000,
+        }
+    }
+}
+
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+// 帳號最近的發文/留言時間
+pub struct RecentWrites {
+    posts: Vec<u64>,
+    comments: Vec<u64>,
+}
+
+#[derive(Clone, Copy)]
+// 受頻率限制的操作
+enum WriteKind {
+    Post,
+    Comment,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+// 提案投票的計票方式
+pub enum VotingMode {
+    // 每個帳號一票
+    ByAccount,
+    // 依聲望計票
+    ByKarma,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 提案設定
+pub struct GovernanceConfig {
+    // 計票方式（管理員與合約擁有者設定相關的提案一律依聲望計票）
+    voting_mode: VotingMode,
+    // 每個帳號一票時，投票總數（贊成 + 反對）至少要達到多少提案才有效
+    account_quorum: u64,
+    // 依聲望計票時，投票的聲望總數至少要達到多少提案才有效
+    karma_quorum: u64,
+    // 投票期間（奈秒）
+    voting_period: u64,
+    // 提出提案所需的聲望
+    min_karma_to_propose: u64,
+    // 投票所需的聲望（只計算提案前karma_cutoff以前就取得的聲望）
+    min_karma_to_vote: u64,
+    // 帳號第一次發文或留言後要經過多久，才能對之後提出的提案投票（奈秒）
+    min_account_age: u64,
+    // 提案前多久以前取得的聲望才能用來投票（奈秒），避免提案後才互相點讚灌聲望
+    karma_cutoff: u64,
+}
+
+impl Default for GovernanceConfig {
+    fn default() -> Self {
+        Self {
+            voting_mode: VotingMode::ByKarma,
+            account_quorum: 50,
+            karma_quorum: 1_000,
+            // 7天
+            voting_period: 7 * 24 * 60 * 60 * 1_000_000_000,
+            min_karma_to_propose: 10,
+            min_karma_to_vote: 1,
+            // 30天
+            min_account_age: 30 * 24 * 60 * 60 * 1_000_000_000,
+            // 7天
+            karma_cutoff: 7 * 24 * 60 * 60 * 1_000_000_000,
+        }
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 提案通過後執行的動作（與合約擁有者/管理員使用的管理方法相同）
+pub enum ProposalAction {
+    // 新增管理員
+    AddModerator {
+        account_id: AccountId,
+    },
+    // 移除管理員
+    RemoveModerator {
+        account_id: AccountId,
+    },
+    // 設定內容長度與數量限制
+    SetLimits {
+        limits: ContentLimits,
+    },
+    // 新增看板
+    CreateBoard {
+        name: String,
+        description: String,
+        restricted: bool,
+        auto_lock_after: Option<u64>,
+    },
+}
+
+impl ProposalAction {
+    // 是否為變更管理員名單或合約擁有者設定的動作（不能用每個帳號一票的方式決定）
+    fn is_privileged(&self) -> bool {
+        matches!(
+            self,
+            ProposalAction::AddModerator { .. }
+                | ProposalAction::RemoveModerator { .. }
+                | ProposalAction::SetLimits { .. }
+        )
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+// 提案狀態
+pub enum ProposalStatus {
+    // 投票中
+    InProgress,
+    // 通過並已執行
+    Approved,
+    // 未通過
+    Rejected,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 提案
+pub struct Proposal {
+    // 提案流水號
+    id: u64,
+    // 提案者
+    proposer: AccountId,
+    // 提案說明
+    description: String,
+    // 通過後執行的動作
+    action: ProposalAction,
+    // 計票方式（提案時決定，之後修改設定不會影響）
+    voting_mode: VotingMode,
+    // 提案時間（之後才開始活動的帳號不能投票）
+    created_at: u64,
+    // 只計算這個時間以前取得的聲望（提案時決定）
+    karma_cutoff_at: u64,
+    // 投票總數的門檻（提案時依計票方式決定）
+    quorum: u64,
+    // 投票截止時間
+    ends_at: u64,
+    // 贊成票數
+    yes: u64,
+    // 反對票數
+    no: u64,
+    // 已經投過票的帳號
+    voters: Vec<AccountId>,
+    // 狀態
+    status: ProposalStatus,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+// 合約設定
+pub struct Config {
+    // 各項權限所需的聲望
+    karma_thresholds: KarmaThresholds,
+    // 可以使用的表情符號
+    allowed_emojis: Vec<String>,
+    // 被多少個帳號檢舉後自動隱藏（null代表不自動隱藏）
+    auto_hide_report_threshold: Option<u64>,
+    // 發文/留言頻率限制
+    rate_limits: RateLimits,
+    // 內容長度與數量限制
+    limits: ContentLimits,
+    // 移除後可以復原的期間（奈秒）
+    restore_grace_period: u64,
+    // 打賞的平台抽成（萬分比）
+    tip_fee_basis_points: u16,
+    // 看板持有資格確認後的有效期間（奈秒）
+    membership_ttl: u64,
+    // 文章NFT售出時給原作者的版稅（萬分比）
+    nft_royalty_basis_points: u16,
+    // 提案設定
+    governance: GovernanceConfig,
+    // 懸賞截止後留給管理員選出最佳解答的期間（奈秒），之後才能退還懸賞
+    bounty_grace_period: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            karma_thresholds: KarmaThresholds::default(),
+            allowed_emojis: ["👍", "❤️", "😂", "😮", "😢", "🎉"]
+                .iter()
+                .map(|emoji| emoji.to_string())
+                .collect(),
+            auto_hide_report_threshold: None,
+            rate_limits: RateLimits::default(),
+            limits: ContentLimits::default(),
+            // 30天
+            restore_grace_period: 30 * 24 * 60 * 60 * 1_000_000_000,
+            tip_fee_basis_points: 0,
+            // 1天
+            membership_ttl: 24 * 60 * 60 * 1_000_000_000,
+            nft_royalty_basis_points: 1_000,
+            governance: GovernanceConfig::default(),
+            // 3天
+            bounty_grace_period: 3 * 24 * 60 * 60 * 1_000_000_000,
+        }
+    }
+}
+
+// 舊版合約（只有文章、留言與子留言）的資料格式，僅供migrate讀取舊資料
+#[derive(BorshSerialize, BorshDeserialize)]
+struct OldPost {
+    id: u128,
+    title: String,
+    content: String,
+    tags: Vec<String>,
+    users_who_liked: Vec<AccountId>,
+    creator_user_id: AccountId,
+    comments: Vec<WithStatus<OldComment>>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct OldComment {
+    comment_creator_user_id: AccountId,
+    content: String,
+    users_who_liked: Vec<AccountId>,
+    sub_comments: Vec<WithStatus<OldSubComment>>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct OldSubComment {
+    comment_creator_user_id: AccountId,
+    content: String,
+    users_who_liked: Vec<AccountId>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct OldBulletinBoard {
+    posts: UnorderedMap<u128, WithStatus<OldPost>>,
+    tags: UnorderedMap<String, Vec<u128>>,
+    number_of_posts: u128,
+    likes_by_user_id: UnorderedMap<AccountId, Vec<OldPost>>,
+}
+
+#[near_bindgen]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BulletinBoard {
+    posts: UnorderedMap<u128, WithStatus<Post>>,
+    tags: UnorderedMap<String, Vec<u128>>,
+    number_of_posts: u128,
+    likes_by_user_id: UnorderedMap<AccountId, Vec<Post>>,
+    // 合約擁有者
+    owner_id: AccountId,
+    // 管理員
+    moderators: UnorderedSet<AccountId>,
+    // 個人資料
+    profiles: UnorderedMap<AccountId, Profile>,
+    // 聲望（文章與留言收到的讚數）
+    karma: UnorderedMap<AccountId, u64>,
+    // 看板
+    boards: UnorderedMap<String, Board>,
+    // 合約設定
+    config: Config,
+    // 各對象的表情符號計數
+    emoji_counts: UnorderedMap<ContentTarget, Vec<(String, u64)>>,
+    // 各帳號在各對象使用的表情符號
+    emoji_reactions_by_user: LookupMap<(ContentTarget, AccountId), Vec<String>>,
+    // 檢舉案件
+    reports: UnorderedMap<ContentTarget, ReportCase>,
+    // 封鎖名單
+    bans: UnorderedMap<AccountId, Vec<Ban>>,
+    // 不受頻率限制的帳號
+    rate_limit_exempt: UnorderedSet<AccountId>,
+    // 各帳號最近的發文/留言時間
+    recent_writes: LookupMap<AccountId, RecentWrites>,
+    // 各對象被哪些文章與留言引用
+    backlinks: LookupMap<ContentTarget, Vec<ContentTarget>>,
+    // 各帳號的通知收件匣
+    inboxes: LookupMap<AccountId, Inbox>,
+    // 各帳號發表的文章
+    posts_by_author: LookupMap<AccountId, Vec<u128>>,
+    // 各帳號追蹤的帳號與標籤
+    following: LookupMap<AccountId, Following>,
+    // 各帳號的追蹤者
+    followers: LookupMap<AccountId, Vec<AccountId>>,
+    // 各帳號收藏的文章
+    bookmarks: LookupMap<AccountId, Vec<Bookmark>>,
+    // 各文章與留言收到的打賞總額（扣除平台抽成後）
+    tips: LookupMap<ContentTarget, Balance>,
+    // 各作者收到的打賞總額（扣除平台抽成後）
+    tips_by_author: LookupMap<AccountId, Balance>,
+    // 可以用來打賞的代幣合約
+    ft_whitelist: UnorderedSet<AccountId>,
+    // 各帳號收到的代幣打賞餘額（帳號, 代幣合約）
+    ft_balances: LookupMap<(AccountId, AccountId), Balance>,
+    // 各帳號在各看板的持有資格有效期限（帳號, 看板）
+    memberships: LookupMap<(AccountId, String), u64>,
+    // 文章NFT（token_id就是文章流水號）
+    tokens: NonFungibleToken,
+    // 付費文章的購買紀錄（文章流水號, 購買者）與購買時間
+    purchases: LookupMap<(u128, AccountId), u64>,
+    // 各作者可以提領的付費文章收入
+    premium_revenue: LookupMap<AccountId, Balance>,
+    // 提案
+    proposals: UnorderedMap<u64, Proposal>,
+    number_of_proposals: u64,
+    // 站內信
+    messages: LookupMap<u64, Message>,
+    number_of_messages: u64,
+    // 各帳號收件匣與寄件備份中的站內信
+    inbox_messages: LookupMap<AccountId, Vec<u64>>,
+    outbox_messages: LookupMap<AccountId, Vec<u64>>,
+    // 各帳號拒收站內信的帳號
+    blocked_accounts: LookupMap<AccountId, Vec<AccountId>>,
+    // 打賞的平台抽成中，合約擁有者還沒提領的金額
+    collected_fees: Balance,
+    // 各帳號第一次發文或留言的時間
+    joined_at: LookupMap<AccountId, u64>,
+    // 站內信加密用的公鑰（其他帳號用這把公鑰加密要寄給自己的站內信）
+    public_keys: LookupMap<AccountId, String>,
+    // 各帳號的聲望紀錄（區間開始時間, 區間結束時的聲望），投票時用來查詢提案前的聲望
+    karma_history: LookupMap<AccountId, Vec<(u64, u64)>>,
+}
+
+impl Default for BulletinBoard {
+    fn default() -> Self {
+        Self {
+            posts: UnorderedMap::new(b'm'),
+            tags: UnorderedMap::new(b'n'),
+            number_of_posts: 0,
+            likes_by_user_id: UnorderedMap::new(b'o'),
+            owner_id: env::current_account_id(),
+            moderators: UnorderedSet::new(b'p'),
+            profiles: UnorderedMap::new(b'q'),
+            karma: UnorderedMap::new(b'r'),
+            boards: UnorderedMap::new(b's'),
+            config: Config::default(),
+            emoji_counts: UnorderedMap::new(b't'),
+            emoji_reactions_by_user: LookupMap::new(b'u'),
+            reports: UnorderedMap::new(b'v'),
+            bans: UnorderedMap::new(b'w'),
+            rate_limit_exempt: UnorderedSet::new(b'x'),
+            recent_writes: LookupMap::new(b'y'),
+            backlinks: LookupMap::new(b'z'),
+            inboxes: LookupMap::new(b'A'),
+            posts_by_author: LookupMap::new(b'B'),
+            following: LookupMap::new(b'C'),
+            followers: LookupMap::new(b'D'),
+            bookmarks: LookupMap::new(b'E'),
+            tips: LookupMap::new(b'F'),
+            tips_by_author: LookupMap::new(b'G'),
+            ft_whitelist: UnorderedSet::new(b'H'),
+            ft_balances: LookupMap::new(b'I'),
+            memberships: LookupMap::new(b'J'),
+            tokens: NonFungibleToken::new(
+                b'K',
+                env::current_account_id(),
+                Some(b'L'),
+                Some(b'M'),
+                Some(b'N'),
+            ),
+            purchases: LookupMap::new(b'O'),
+            premium_revenue: LookupMap::new(b'P'),
+            proposals: UnorderedMap::new(b'Q'),
+            number_of_proposals: 0,
+            messages: LookupMap::new(b'R'),
+            number_of_messages: 0,
+            inbox_messages: LookupMap::new(b'S'),
+            outbox_messages: LookupMap::new(b'T'),
+            blocked_accounts: LookupMap::new(b'U'),
+            collected_fees: 0,
+            joined_at: LookupMap::new(b'V'),
+            public_keys: LookupMap::new(b'W'),
+            karma_history: LookupMap::new(b'X'),
+        }
+    }
+}
+
+#[near_bindgen]
+impl BulletinBoard {
+    // 確認請求的status參數是否正確
+    fn check_status_string(str: &String) -> bool {
+        str == "Open" || str == "Locked" || str == "Removed"
+    }
+
+    // 確認是否為管理員（合約擁有者也視為管理員）
+    fn is_moderator(&self, account_id: &AccountId) -> bool {
+        *account_id == self.owner_id || self.moderators.contains(account_id)
+    }
+
+    // 標籤正規化：去除前後空白、轉成小寫，並檢查長度與字元
+    fn normalize_tag(&self, tag: &str) -> Result<String, BoardError> {
+        let normalized_tag = tag.trim().to_lowercase();
+        // 長度不符
+        if normalized_tag.is_empty() {
+            return Err(BoardError::EmptyField { field: "tag" });
+        }
+        if normalized_tag.chars().count() > self.config.limits.max_tag_length as usize {
+            return Err(BoardError::TooLong {
+                field: "tag",
+                max: self.config.limits.max_tag_length,
+                unit: "characters",
+            });
+        }
+        // 只接受文字、數字、「-」與「_」
+        if normalized_tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            Ok(normalized_tag)
+        } else {
+            Err(BoardError::InvalidTag {
+                tag: tag.to_string(),
+            })
+        }
+    }
+
+    // 正規化請求中的所有標籤，有任何一個不合法或數量超過上限就回傳錯誤
+    fn normalize_tags(&self, tags: Vec<String>) -> Result<Vec<String>, BoardError> {
+        let mut normalized_tags: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = self.normalize_tag(&tag)?;
+            // 正規化後可能會重複（例如「Rust」與「rust 」），只留一個
+            if !normalized_tags.contains(&tag) {
+                normalized_tags.push(tag);
+            }
+        }
+        if normalized_tags.len() > self.config.limits.max_tags_per_post as usize {
+            Err(BoardError::TooManyTags {
+                max: self.config.limits.max_tags_per_post,
+            })
+        } else {
+            Ok(normalized_tags)
+        }
+    }
+
+    // 確認標題長度，不符合就中止這次呼叫
+    fn assert_valid_title(&self, title: &str) {
+        if title.trim().is_empty() {
+            BoardError::EmptyField { field: "title" }.panic();
+        }
+        if title.chars().count() > self.config.limits.max_title_length as usize {
+            BoardError::TooLong {
+                field: "title",
+                max: self.config.limits.max_title_length,
+                unit: "characters",
+            }
+            .panic();
+        }
+    }
+
+    // 確認內文長度，不符合就中止這次呼叫
+    fn assert_valid_content(&self, content: &str) {
+        if content.trim().is_empty() {
+            BoardError::EmptyField { field: "content" }.panic();
+        }
+        if content.len() > self.config.limits.max_content_bytes as usize {
+            BoardError::TooLong {
+                field: "content",
+                max: self.config.limits.max_content_bytes,
+                unit: "bytes",
+            }
+            .panic();
+        }
+    }
+
+    // 確認留言/回覆長度，不符合就中止這次呼叫
+    fn assert_valid_comment(&self, content: &str, is_reply: bool) {
+        let (field, max) = if is_reply {
+            ("reply", self.config.limits.max_reply_length)
+        } else {
+            ("comment", self.config.limits.max_comment_length)
+        };
+        if content.trim().is_empty() {
+            BoardError::EmptyField { field }.panic();
+        }
+        if content.chars().count() > max as usize {
+            BoardError::TooLong {
+                field,
+                max,
+                unit: "characters",
+            }
+            .panic();
+        }
+    }
+
+    // 確認投票設定，不符合就中止這次呼叫
+    fn validate_poll(&self, poll: NewPoll) -> Poll {
+        let max_options = self.config.limits.max_poll_options;
+        if poll.options.len() < 2 {
+            BoardError::InvalidPoll {
+                reason: "a poll needs at least 2 options",
+            }
+            .panic();
+        }
+        if poll.options.len() > max_options as usize {
+            BoardError::LimitReached {
+                what: "poll options",
+                max: max_options,
+            }
+            .panic();
+        }
+        poll.options.iter().for_each(|option| {
+            if option.trim().is_empty() {
+                BoardError::EmptyField {
+                    field: "poll option",
+                }
+                .panic();
+            }
+            if option.chars().count() > self.config.limits.max_poll_option_length as usize {
+                BoardError::TooLong {
+                    field: "poll option",
+                    max: self.config.limits.max_poll_option_length,
+                    unit: "characters",
+                }
+                .panic();
+            }
+        });
+        if poll.ends_at <= env::block_timestamp() {
+            BoardError::InvalidPoll {
+                reason: "the end time must be in the future",
+            }
+            .panic();
+        }
+        Poll {
+            counts: vec![0; poll.options.len()],
+            options: poll.options,
+            multiple_choice: poll.multiple_choice,
+            ends_at: poll.ends_at,
+            voters_visible: poll.voters_visible,
+            votes: Vec::default(),
+        }
+    }
+
+    // 將文章ID加入標籤索引
+    fn index_tags(&mut self, post_id: u128, tags: &[String]) {
+        tags.iter().for_each(|tag| {
+            let mut posts_id_vec = self.tags.get(tag).unwrap_or_default();
+            if !posts_id_vec.contains(&post_id) {
+                posts_id_vec.push(post_id);
+            }
+            self.tags.insert(tag, &posts_id_vec);
+        });
+    }
+
+    // 將文章ID從標籤索引移除，沒有文章的標籤一併移除
+    fn unindex_tags(&mut self, post_id: u128, tags: &[String]) {
+        tags.iter().for_each(|tag| {
+            if let Some(mut posts_id_vec) = self.tags.get(tag) {
+                posts_id_vec.retain(|id| *id != post_id);
+                if posts_id_vec.is_empty() {
+                    self.tags.remove(tag);
+                } else {
+                    self.tags.insert(tag, &posts_id_vec);
+                }
+            }
+        });
+    }
+
+    // 查詢帳號的顯示名稱（沒設定就是None）
+    fn display_name_of(&self, account_id: &AccountId) -> Option<String> {
+        self.profiles
+            .get(account_id)
+            .map(|profile| profile.display_name)
+            .filter(|display_name| !display_name.is_empty())
+    }
+
+    // 有要求的話，在文章與留言中填入作者的顯示名稱
+    fn with_display_names(
+        &self,
+        mut posts: Vec<(u128, WithStatus<Post>)>,
+        with_display_name: Option<bool>,
+    ) -> Vec<(u128, WithStatus<Post>)> {
+        if with_display_name.unwrap_or(false) {
+            posts.iter_mut().for_each(|(_, post_with_status)| {
+                if let Some(post) = post_with_status.inner_mut() {
+                    post.creator_display_name = self.display_name_of(&post.creator_user_id);
+                    post.comments.iter_mut().for_each(|comment_with_status| {
+                        if let Some(comment) = comment_with_status.inner_mut() {
+                            comment.creator_display_name =
+                                self.display_name_of(&comment.comment_creator_user_id);
+                        }
+                    });
+                }
+            });
+        }
+        posts
+    }
+
+    // 記錄作者的文章（只保留最新的幾篇，動態只需要最近的文章）
+    fn index_author_post(&mut self, author: &AccountId, post_id: u128) {
+        let mut posts_by_author = self.posts_by_author.get(author).unwrap_or_default();
+        posts_by_author.push(post_id);
+        let max = self.config.limits.max_feed_posts_per_author as usize;
+        if posts_by_author.len() > max {
+            posts_by_author.drain(..posts_by_author.len() - max);
+        }
+        self.posts_by_author.insert(author, &posts_by_author);
+    }
+
+    // 由呼叫者支付這次呼叫增加的儲存空間費用，多付的押金退回
+    // 押金是由predecessor附加的（透過其他合約呼叫時就是那個合約），所以退給predecessor
+    fn charge_storage(&self, initial_storage_usage: u64) {
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        let storage_cost = Balance::from(storage_used) * env::storage_byte_cost();
+        let attached_deposit = env::attached_deposit();
+        if attached_deposit < storage_cost {
+            BoardError::InsufficientDeposit {
+                required: storage_cost,
+            }
+            .panic();
+        }
+        let refund = attached_deposit - storage_cost;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    // 把這次呼叫釋放的儲存空間費用退給當初支付的帳號
+    fn refund_storage(&self, initial_storage_usage: u64, account_id: &AccountId) {
+        let storage_freed = initial_storage_usage.saturating_sub(env::storage_usage());
+        let refund = Balance::from(storage_freed) * env::storage_byte_cost();
+        if refund > 0 {
+            Promise::new(account_id.clone()).transfer(refund);
+        }
+    }
+
+    // 退還這次呼叫附加的押金（與charge_storage一樣退給predecessor）
+    fn refund_deposit(&self) {
+        let attached_deposit = env::attached_deposit();
+        if attached_deposit > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(attached_deposit);
+        }
+    }
+
+    // 結算懸賞：有最佳解答就發給解答的留言者，否則退還給作者，結算後文章會被鎖定
+    fn settle_bounty(
+        &mut self,
+        post_id: u128,
+        accepted_comment_id: Option<u128>,
+    ) -> WithStatus<Post> {
+        let mut post_with_status = match self.load_post(post_id) {
+            Some(post_with_status) => post_with_status,
+            None => return Empty,
+        };
+        let post = match post_with_status.inner_mut() {
+            Some(post) => post,
+            None => return Empty,
+        };
+        let receiver = match accepted_comment_id {
+            Some(comment_id) => match post.comment(comment_id) {
+                Some(Open(comment) | Locked(comment)) => comment.comment_creator_user_id.clone(),
+                _ => return Empty,
+            },
+            None => post.creator_user_id.clone(),
+        };
+        let bounty = match post.bounty.as_mut() {
+            Some(bounty) if !bounty.settled => bounty,
+            _ => return Empty,
+        };
+        bounty.accepted_comment_id = accepted_comment_id;
+        bounty.settled = true;
+        Promise::new(receiver).transfer(bounty.amount.0);
+        // 被移除的文章維持移除
+        if matches!(post_with_status, Open(_)) {
+            post_with_status.set_status("Locked");
+        }
+        self.posts.insert(&post_id, &post_with_status);
+        post_with_status
+    }
+
+    // 把附加的押金扣除平台抽成後轉給作者，轉帳成功後才記錄打賞
+    fn send_tip(&mut self, target: ContentTarget) -> Promise {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(target.post_id());
+        let amount = env::attached_deposit();
+        if amount == 0 {
+            BoardError::CannotTip {
+                reason: "attach a deposit to tip",
+            }
+            .panic();
+        }
+        // 被移除的文章或留言不能打賞
+        let author = match self.target_author(&target) {
+            Open(author) | Locked(author) => author,
+            _ => BoardError::CannotTip {
+                reason: "the post or comment does not exist or has been removed",
+            }
+            .panic(),
+        };
+        if author == env::signer_account_id() {
+            BoardError::CannotTip {
+                reason: "cannot tip yourself",
+            }
+            .panic();
+        }
+        let fee = amount * Balance::from(self.config.tip_fee_basis_points) / 10_000;
+        Promise::new(author.clone()).transfer(amount - fee).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(TIP_CALLBACK_GAS)
+                .on_tip_transferred(
+                    target,
+                    env::predecessor_account_id(),
+                    author,
+                    U128(amount - fee),
+                    U128(fee),
+                ),
+        )
+    }
+
+    // 查詢帳號的聲望
+    fn karma_of(&self, account_id: &AccountId) -> u64 {
+        self.karma.get(account_id).unwrap_or(0)
+    }
+
+    // 內容被點讚時聲望+1，被取消點讚時聲望-1
+    fn change_karma(&mut self, account_id: &AccountId, liked: bool) {
+        let karma = self.karma_of(account_id);
+        let karma = if liked {
+            karma + 1
+        } else {
+            karma.saturating_sub(1)
+        };
+        self.set_karma(account_id, karma);
+    }
+
+    // 更新帳號的聲望，並記錄到聲望紀錄中
+    fn set_karma(&mut self, account_id: &AccountId, karma: u64) {
+        self.karma.insert(account_id, &karma);
+        let now = env::block_timestamp();
+        let interval_start = now - now % KARMA_CHECKPOINT_INTERVAL;
+        let mut history = self.karma_history.get(account_id).unwrap_or_default();
+        match history.last_mut() {
+            Some((last_start, last_karma)) if *last_start == interval_start => *last_karma = karma,
+            _ => history.push((interval_start, karma)),
+        }
+        // 只保留投票中的提案可能會查詢到的紀錄，更早的紀錄只留最後一筆當作基準
+        let governance = &self.config.governance;
+        let horizon = now.saturating_sub(
+            governance.voting_period + governance.karma_cutoff + KARMA_CHECKPOINT_INTERVAL,
+        );
+        let stale = history
+            .iter()
+            .take_while(|(start, _)| start + KARMA_CHECKPOINT_INTERVAL <= horizon)
+            .count();
+        if stale > 1 {
+            history.drain(..stale - 1);
+        }
+        self.karma_history.insert(account_id, &history);
+    }
+
+    // 查詢帳號在指定時間以前取得的聲望（只計算在那之前就已經結束的區間）
+    fn karma_at(&self, account_id: &AccountId, timestamp: u64) -> u64 {
+        self.karma_history
+            .get(account_id)
+            .unwrap_or_default()
             .into_iter()
-            // 僅有狀態為開放與鎖定的文章可以被查詢到
-            .filter(|(_, post_with_status)| match post_with_status {
-                Open(post) | Locked(post) => post.title.contains(&q) || post.content.contains(&q),
-                _ => false,
+            .rev()
+            .find(|(start, _)| start + KARMA_CHECKPOINT_INTERVAL <= timestamp)
+            .map_or(0, |(_, karma)| karma)
+    }
+
+    // 確認帳號是否有足夠的聲望建立還不存在的標籤
+    fn can_create_tags(&self, account_id: &AccountId, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.get(tag).is_some())
+            || self.karma_of(account_id) >= self.config.karma_thresholds.create_tag
+    }
+
+    // 確認帳號是否可以在看板發文（看板必須存在，限制看板需要足夠的聲望）
+    fn can_post_in_board(&self, account_id: &AccountId, board: &String) -> bool {
+        match self.boards.get(board) {
+            None => false,
+            Some(board) => {
+                !board.restricted
+                    || self.karma_of(account_id)
+                        >= self.config.karma_thresholds.post_in_restricted_board
+            }
+        }
+    }
+
+    // 新增管理員（不檢查身份，由呼叫的地方負責）
+    fn internal_add_moderator(&mut self, account_id: &AccountId) -> bool {
+        self.moderators.insert(account_id)
+    }
+
+    // 移除管理員（不檢查身份，由呼叫的地方負責）
+    fn internal_remove_moderator(&mut self, account_id: &AccountId) -> bool {
+        self.moderators.remove(account_id)
+    }
+
+    // 設定內容長度與數量限制（不檢查身份，由呼叫的地方負責）
+    fn internal_set_limits(&mut self, limits: ContentLimits) -> bool {
+        self.config.limits = limits;
+        true
+    }
+
+    // 新增看板（不檢查身份，由呼叫的地方負責），看板名稱不能重複
+    fn internal_create_board(
+        &mut self,
+        name: String,
+        description: String,
+        restricted: bool,
+        auto_lock_after: Option<u64>,
+    ) -> bool {
+        if self.boards.get(&name).is_some() {
+            return false;
+        }
+        self.boards.insert(
+            &name,
+            &Board {
+                name: name.clone(),
+                description,
+                restricted,
+                auto_lock_after,
+                gate: None,
+            },
+        );
+        true
+    }
+
+    // 執行提案的動作
+    fn execute_proposal_action(&mut self, action: ProposalAction) -> bool {
+        match action {
+            ProposalAction::AddModerator { account_id } => self.internal_add_moderator(&account_id),
+            ProposalAction::RemoveModerator { account_id } => {
+                self.internal_remove_moderator(&account_id)
+            }
+            ProposalAction::SetLimits { limits } => self.internal_set_limits(limits),
+            ProposalAction::CreateBoard {
+                name,
+                description,
+                restricted,
+                auto_lock_after,
+            } => self.internal_create_board(name, description, restricted, auto_lock_after),
+        }
+    }
+
+    // 帳號在提案時是否已經有投票資格（提案前就已經活動了足夠久，且有足夠的聲望）
+    fn can_vote_on(&self, account_id: &AccountId, proposal: &Proposal) -> bool {
+        let governance = &self.config.governance;
+        self.karma_at(account_id, proposal.karma_cutoff_at) >= governance.min_karma_to_vote
+            && self.joined_at.get(account_id).is_some_and(|joined_at| {
+                joined_at.saturating_add(governance.min_account_age) <= proposal.created_at
             })
-            .collect::<Vec<(u128, WithStatus<Post>)>>()
     }
 
-    // 透過標籤查詢文章
-    pub fn search_post_by_tags(&self, tags: Vec<String>) -> Vec<(u128, WithStatus<Post>)> {
-        self.posts
-            .to_vec()
-            .into_iter()
-            // 僅有狀態為開放與鎖定的文章可以被查詢到
-            .filter(|(_, post_with_status)| match post_with_status {
-                Open(post) | Locked(post) => tags.iter().all(|tag| post.tags.contains(tag)),
-                _ => false,
-            })
-            .collect::<Vec<(u128, WithStatus<Post>)>>()
+    // 投票截止後結算提案：投票總數達到門檻且贊成多於反對就通過並執行
+    fn finalize(&mut self, mut proposal: Proposal) -> Proposal {
+        if proposal.status != ProposalStatus::InProgress
+            || env::block_timestamp() < proposal.ends_at
+        {
+            return proposal;
+        }
+        if proposal.yes + proposal.no >= proposal.quorum && proposal.yes > proposal.no {
+            self.execute_proposal_action(proposal.action.clone());
+            proposal.status = ProposalStatus::Approved;
+        } else {
+            proposal.status = ProposalStatus::Rejected;
+        }
+        self.proposals.insert(&proposal.id, &proposal);
+        proposal
+    }
+
+    // 把帳號加入/移出點讚名單，沒有變動就回傳false
+    fn toggle_like(
+        users_who_liked: &mut Vec<AccountId>,
+        account_id: &AccountId,
+        like: bool,
+    ) -> bool {
+        match (
+            like,
+            users_who_liked
+                .iter()
+                .position(|user_id| user_id == account_id),
+        ) {
+            // 還沒點過讚，將使用者ID存入
+            (true, None) => {
+                users_who_liked.push(account_id.clone());
+                true
+            }
+            // 已經點過讚，將使用者ID透過index移除
+            (false, Some(index)) => {
+                users_who_liked.remove(index);
+                true
+            }
+            // 重複點讚或沒點過讚卻要取消，不做任何事
+            _ => false,
+        }
+    }
+
+    // 設定帳號的推文（None代表收回），並更新推文統計，沒有變動就回傳false
+    fn set_reaction(
+        reactions: &mut Vec<(AccountId, Reaction)>,
+        reaction_tally: &mut ReactionTally,
+        account_id: &AccountId,
+        reaction: Option<Reaction>,
+    ) -> bool {
+        let index = reactions
+            .iter()
+            .position(|(user_id, _)| user_id == account_id);
+        match (index, reaction) {
+            // 沒有推文也沒有要收回，不做任何事
+            (None, None) => false,
+            // 新的推文
+            (None, Some(reaction)) => {
+                reactions.push((account_id.clone(), reaction));
+                *reaction_tally.count_mut(reaction) += 1;
+                true
+            }
+            // 跟原本的推文一樣，不做任何事
+            (Some(index), Some(reaction)) if reactions[index].1 == reaction => false,
+            // 收回或改變推文，先把原本的推文扣掉
+            (Some(index), reaction) => {
+                let (_, original_reaction) = reactions.remove(index);
+                *reaction_tally.count_mut(original_reaction) -= 1;
+                if let Some(reaction) = reaction {
+                    reactions.push((account_id.clone(), reaction));
+                    *reaction_tally.count_mut(reaction) += 1;
+                }
+                true
+            }
+        }
+    }
+
+    // 查詢操作對象的作者與狀態（上層被鎖定或移除時，下層也視為相同狀態）
+    fn target_author(&self, target: &ContentTarget) -> WithStatus<AccountId> {
+        let post_with_status = match self.load_post(target.post_id()) {
+            Some(post_with_status) => post_with_status,
+            None => return Empty,
+        };
+        let mut locked = matches!(post_with_status, Locked(_));
+        let mut removed = matches!(post_with_status, Removed(_));
+        let post = match post_with_status.inner() {
+            Some(post) => post,
+            None => return Empty,
+        };
+        let mut author = post.creator_user_id.clone();
+        // 對象是留言，沿著回覆的留言往上找
+        if let Some(comment_id) = target.comment_id() {
+            let mut current = Some(comment_id);
+            while let Some(comment_id) = current {
+                let comment_with_status = match post.comment(comment_id) {
+                    Some(comment_with_status) => comment_with_status,
+                    None => return Empty,
+                };
+                locked |= matches!(comment_with_status, Locked(_));
+                removed |= matches!(comment_with_status, Removed(_));
+                let comment = match comment_with_status.inner() {
+                    Some(comment) => comment,
+                    None => return Empty,
+                };
+                if Some(comment.id) == target.comment_id() {
+                    author = comment.comment_creator_user_id.clone();
+                }
+                current = comment.parent_id;
+            }
+        }
+        if removed {
+            Removed(author)
+        } else if locked {
+            Locked(author)
+        } else {
+            Open(author)
+        }
+    }
+
+    // 查詢對象本身的狀態（不套用自動鎖定，也不考慮上層的文章與留言）
+    fn target_status(&self, target: &ContentTarget) -> Option<String> {
+        let post_with_status = self.posts.get(&target.post_id())?;
+        let status = match target.comment_id() {
+            None => post_with_status.status_string(),
+            Some(comment_id) => post_with_status
+                .inner()?
+                .comment(comment_id)?
+                .status_string(),
+        };
+        status.map(|status| status.to_string())
+    }
+
+    // 直接修改對象的狀態（不檢查身份與原本的狀態，由呼叫的地方負責）
+    fn set_target_status(&mut self, target: &ContentTarget, status: &str) -> bool {
+        let mut post_with_status = match self.posts.get(&target.post_id()) {
+            Some(post_with_status) => post_with_status,
+            None => return false,
+        };
+        match target.comment_id() {
+            // 文章
+            None => {
+                // 更新標籤索引，移除的文章不列入索引
+                if let Some(post) = post_with_status.inner() {
+                    let (post_id, tags) = (post.id, post.tags.clone());
+                    if status == "Removed" {
+                        self.unindex_tags(post_id, &tags);
+                    } else {
+                        self.index_tags(post_id, &tags);
+                    }
+                }
+                post_with_status.set_status(status);
+            }
+            // 留言
+            Some(comment_id) => {
+                match post_with_status
+                    .inner_mut()
+                    .and_then(|post| post.comment_mut(comment_id))
+                {
+                    Some(comment_with_status) => comment_with_status.set_status(status),
+                    None => return false,
+                }
+            }
+        }
+        // 儲存文章
+        self.posts.insert(&target.post_id(), &post_with_status);
+        true
+    }
+
+    // 清除對象的移除者（自動隱藏等不是由帳號移除的情況）
+    fn clear_removed_by(&mut self, target: &ContentTarget) {
+        if let Some(mut post_with_status) = self.posts.get(&target.post_id()) {
+            let removed_by = match target.comment_id() {
+                None => post_with_status
+                    .inner_mut()
+                    .map(|post| &mut post.removed_by),
+                Some(comment_id) => post_with_status
+                    .inner_mut()
+                    .and_then(|post| post.comment_mut(comment_id))
+                    .and_then(|comment_with_status| comment_with_status.inner_mut())
+                    .map(|comment| &mut comment.removed_by),
+            };
+            if let Some(removed_by) = removed_by {
+                *removed_by = None;
+                self.posts.insert(&target.post_id(), &post_with_status);
+            }
+        }
+    }
+
+    // 查詢帳號在看板（或全站）有效的封鎖
+    fn active_ban(&self, account_id: &AccountId, board: Option<&String>) -> Option<Ban> {
+        self.bans
+            .get(account_id)
+            .unwrap_or_default()
+            .into_iter()
+            // 全站封鎖在所有看板都有效
+            .find(|ban| ban.is_active() && (ban.board.is_none() || ban.board.as_ref() == board))
+    }
+
+    // 確認呼叫者沒有被封鎖，被封鎖就中止這次呼叫
+    fn assert_not_banned(&self, board: Option<&String>) {
+        if let Some(ban) = self.active_ban(&env::signer_account_id(), board) {
+            BoardError::Banned {
+                board: ban.board,
+                reason: ban.reason,
+                expires_at: ban.expires_at,
+            }
+            .panic();
+        }
+    }
+
+    // 依文章所在的看板確認呼叫者沒有被封鎖
+    fn assert_not_banned_in_post(&self, post_id: u128) {
+        let board = self
+            .posts
+            .get(&post_id)
+            .and_then(|post_with_status| post_with_status.into_inner())
+            .and_then(|post| post.board);
+        self.assert_not_banned(board.as_ref());
+    }
+
+    // 確認呼叫者有看板的持有資格（管理員不受限制），沒有就中止這次呼叫
+    fn assert_board_member(&self, board: Option<&String>) {
+        let board = match board {
+            Some(board) => board,
+            None => return,
+        };
+        if self
+            .boards
+            .get(board)
+            .and_then(|board| board.gate)
+            .is_none()
+        {
+            return;
+        }
+        let signer = env::signer_account_id();
+        let expires_at = self
+            .memberships
+            .get(&(signer.clone(), board.clone()))
+            .unwrap_or(0);
+        if expires_at <= env::block_timestamp() && !self.is_moderator(&signer) {
+            BoardError::MembershipRequired {
+                board: board.clone(),
+            }
+            .panic();
+        }
+    }
+
+    // 確認呼叫者有文章所在看板的持有資格
+    fn assert_board_member_in_post(&self, post_id: u128) {
+        let board = self
+            .posts
+            .get(&post_id)
+            .and_then(|post_with_status| post_with_status.into_inner())
+            .and_then(|post| post.board);
+        self.assert_board_member(board.as_ref());
+    }
+
+    // 管理員、被豁免的帳號與聲望足夠的帳號不受頻率限制
+    fn is_rate_limit_exempt(&self, account_id: &AccountId) -> bool {
+        self.is_moderator(account_id)
+            || self.rate_limit_exempt.contains(account_id)
+            || self.karma_of(account_id) >= self.config.karma_thresholds.bypass_rate_limit
+    }
+
+    // 確認呼叫者沒有超過頻率限制，超過就中止這次呼叫（成功後要再用record_write記錄）
+    fn assert_within_rate_limit(&self, kind: WriteKind) {
+        let signer = env::signer_account_id();
+        if self.is_rate_limit_exempt(&signer) {
+            return;
+        }
+        let now = env::block_timestamp();
+        let recent_writes = self.recent_writes.get(&signer).unwrap_or_default();
+        let (timestamps, max, window, action) = match kind {
+            WriteKind::Post => (
+                recent_writes.posts,
+                self.config.rate_limits.max_posts,
+                self.config.rate_limits.post_window,
+                "post",
+            ),
+            WriteKind::Comment => (
+                recent_writes.comments,
+                self.config.rate_limits.max_comments,
+                self.config.rate_limits.comment_window,
+                "comment",
+            ),
+        };
+        // 只計算區間內的紀錄
+        let timestamps = timestamps
+            .into_iter()
+            .filter(|timestamp| timestamp.saturating_add(window) > now)
+            .collect::<Vec<u64>>();
+        if timestamps.len() >= max as usize {
+            // 最舊的一筆紀錄過期後就可以再試
+            BoardError::RateLimited {
+                action,
+                retry_after: timestamps
+                    .iter()
+                    .min()
+                    .copied()
+                    .unwrap_or(now)
+                    .saturating_add(window),
+            }
+            .panic();
+        }
+    }
+
+    // 記錄成功的發文/留言時間（被拒絕的請求不會用掉額度）
+    fn record_write(&mut self, kind: WriteKind) {
+        let signer = env::signer_account_id();
+        // 記錄帳號第一次發文或留言的時間
+        if self.joined_at.get(&signer).is_none() {
+            self.joined_at.insert(&signer, &env::block_timestamp());
+        }
+        if self.is_rate_limit_exempt(&signer) {
+            return;
+        }
+        let now = env::block_timestamp();
+        let mut recent_writes = self.recent_writes.get(&signer).unwrap_or_default();
+        let (timestamps, window) = match kind {
+            WriteKind::Post => (
+                &mut recent_writes.posts,
+                self.config.rate_limits.post_window,
+            ),
+            WriteKind::Comment => (
+                &mut recent_writes.comments,
+                self.config.rate_limits.comment_window,
+            ),
+        };
+        // 只保留計算區間內的紀錄
+        timestamps.retain(|timestamp| timestamp.saturating_add(window) > now);
+        timestamps.push(now);
+        self.recent_writes.insert(&signer, &recent_writes);
+    }
+
+    // 確認帳號是否可以復原移除的內容（在復原期限內）
+    // 原作者只能復原自己移除的內容，管理員移除或被檢舉自動隱藏的內容只有管理員可以復原
+    fn can_restore(
+        &self,
+        account_id: &AccountId,
+        author: &AccountId,
+        removed_at: Option<u64>,
+        removed_by: Option<&AccountId>,
+    ) -> bool {
+        (self.is_moderator(account_id) || (account_id == author && removed_by == Some(author)))
+            && removed_at.is_some_and(|removed_at| {
+                // 舊版合約移除的內容不知道移除時間，不受復原期限限制
+                removed_at == UNKNOWN_TIMESTAMP
+                    || removed_at.saturating_add(self.config.restore_grace_period)
+                        > env::block_timestamp()
+            })
+    }
+
+    // 開放的文章超過自動鎖定的期限就視為鎖定（查詢時才計算，不會寫回鏈上）
+    fn apply_auto_lock(&self, post_with_status: WithStatus<Post>) -> WithStatus<Post> {
+        match post_with_status {
+            Open(post) => {
+                // 文章沒有設定的話，使用看板的設定
+                let auto_lock_after = post.auto_lock_after.or_else(|| {
+                    post.board
+                        .as_ref()
+                        .and_then(|board| self.boards.get(board))
+                        .and_then(|board| board.auto_lock_after)
+                });
+                // 從發文（或最後一次解除鎖定）開始計算，舊版合約的文章不知道發文時間，解除鎖定前不會自動鎖定
+                let since = post.unlocked_at.unwrap_or(post.created_at);
+                match auto_lock_after {
+                    Some(auto_lock_after)
+                        if since != UNKNOWN_TIMESTAMP
+                            && since.saturating_add(auto_lock_after) <= env::block_timestamp() =>
+                    {
+                        Locked(post)
+                    }
+                    _ => Open(post),
+                }
+            }
+            other => other,
+        }
+    }
+
+    // 找出文章，並套用自動鎖定
+    fn load_post(&self, post_id: u128) -> Option<WithStatus<Post>> {
+        self.posts
+            .get(&post_id)
+            .map(|post_with_status| self.apply_auto_lock(post_with_status))
+    }
+
+    // 確認引用的對象都存在且可以查看（開放或鎖定），並去除重複的引用
+    fn validate_references(&self, references: Vec<ContentTarget>) -> Vec<ContentTarget> {
+        let mut validated: Vec<ContentTarget> = Vec::new();
+        references.into_iter().for_each(|target| {
+            if !matches!(self.target_author(&target), Open(_) | Locked(_)) {
+                BoardError::InvalidReference {
+                    post_id: target.post_id(),
+                    comment_id: target.comment_id(),
+                }
+                .panic();
+            }
+            if !validated.contains(&target) {
+                validated.push(target);
+            }
+        });
+        if validated.len() > self.config.limits.max_references as usize {
+            BoardError::TooManyReferences {
+                max: self.config.limits.max_references,
+            }
+            .panic();
+        }
+        validated
+    }
+
+    // 在被引用的對象記錄引用來源
+    fn add_backlinks(&mut self, source: &ContentTarget, references: &[ContentTarget]) {
+        references.iter().for_each(|target| {
+            let mut backlinks = self.backlinks.get(target).unwrap_or_default();
+            backlinks.push(source.clone());
+            self.backlinks.insert(target, &backlinks);
+        });
+    }
+
+    // 查詢引用對象的文章與留言（僅列出目前可以查看的）
+    fn visible_backlinks(&self, target: &ContentTarget) -> Vec<ContentTarget> {
+        self.backlinks
+            .get(target)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|source| matches!(self.target_author(source), Open(_) | Locked(_)))
+            .collect()
+    }
+
+    // 找出文字中提及的帳號（「@」要在開頭或空白之後，且必須是合法的帳號）
+    fn parse_mentions(&self, text: &str) -> Vec<AccountId> {
+        let mut mentions: Vec<AccountId> = Vec::new();
+        let mut previous = ' ';
+        text.char_indices().for_each(|(index, c)| {
+            if c == '@' && previous.is_whitespace() {
+                let candidate = text[index + 1..]
+                    .split(|c: char| {
+                        !(c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c))
+                    })
+                    .next()
+                    .unwrap_or_default()
+                    // 句尾的標點不算在帳號裡
+                    .trim_end_matches(|c| "._-".contains(c));
+                if let Ok(account_id) = candidate.parse::<AccountId>() {
+                    if !mentions.contains(&account_id) {
+                        mentions.push(account_id);
+                    }
+                }
+            }
+            previous = c;
+        });
+        mentions.truncate(self.config.limits.max_mentions as usize);
+        mentions
+    }
+
+    // 在帳號的收件匣加入通知（不會通知自己），超過上限時刪除最舊的通知
+    fn notify(&mut self, account_id: &AccountId, kind: NotificationKind, target: &ContentTarget) {
+        let from = env::signer_account_id();
+        if *account_id == from {
+            return;
+        }
+        let mut inbox = self.inboxes.get(account_id).unwrap_or_default();
+        inbox.next_id += 1;
+        inbox.notifications.push(Notification {
+            id: inbox.next_id,
+            kind,
+            target: target.clone(),
+            from,
+            created_at: env::block_timestamp(),
+            read: false,
+        });
+        let max = self.config.limits.max_notifications_per_account as usize;
+        if inbox.notifications.len() > max {
+            let excess = inbox.notifications.len() - max;
+            inbox.notifications.drain(..excess);
+        }
+        self.inboxes.insert(account_id, &inbox);
+    }
+
+    // 通知文字中提及的帳號（已經收到回覆通知的帳號不重複通知）
+    fn notify_mentions(&mut self, text: &str, target: &ContentTarget, except: Option<&AccountId>) {
+        self.parse_mentions(text)
+            .into_iter()
+            .filter(|account_id| Some(account_id) != except)
+            .for_each(|account_id| self.notify(&account_id, NotificationKind::Mention, target));
+    }
+
+    // 把舊版合約的資料轉換成目前的格式（部署新版合約後由合約帳號呼叫一次）
+    // 子留言會變成回覆原本留言的留言，點讚數會計入作者的聲望
+    // 舊的標籤索引只記錄了每個標籤的第一篇文章，也沒有正規化，所以從轉換後的文章重建
+    // 所有文章會在同一次呼叫中轉換，文章很多的話需要附加足夠的gas
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let mut old: OldBulletinBoard =
+            env::state_read().unwrap_or_else(|| env::panic_str("No state to migrate"));
+        let old_posts = old.posts.to_vec();
+        // 舊的資料會用同樣的prefix重新寫入，先清掉
+        old.posts.clear();
+        old.tags.clear();
+        old.likes_by_user_id.clear();
+        let mut board = Self {
+            number_of_posts: old.number_of_posts,
+            ..Self::default()
+        };
+        old_posts
+            .into_iter()
+            .for_each(|(post_id, old_post_with_status)| {
+                let mut likes: Vec<(AccountId, usize)> = Vec::new();
+                let mut post_with_status = old_post_with_status.map(|old_post| {
+                    let mut comments: Vec<WithStatus<Comment>> = Vec::new();
+                    old_post
+                        .comments
+                        .into_iter()
+                        .for_each(|old_comment_with_status| {
+                            let comment_id = comments.len() as u128;
+                            let mut sub_comments = Vec::new();
+                            let comment_with_status = old_comment_with_status.map(|old_comment| {
+                                sub_comments = old_comment.sub_comments;
+                                Comment {
+                                    id: comment_id,
+                                    comment_creator_user_id: old_comment.comment_creator_user_id,
+                                    content: old_comment.content,
+                                    users_who_liked: old_comment.users_who_liked,
+                                    ..Comment::default()
+                                }
+                            });
+                            if let Some(comment) = comment_with_status.inner() {
+                                likes.push((
+                                    comment.comment_creator_user_id.clone(),
+                                    comment.users_who_liked.len(),
+                                ));
+                            }
+                            comments.push(comment_with_status);
+                            // 子留言接在原本的留言後面
+                            sub_comments
+                                .into_iter()
+                                .for_each(|old_sub_comment_with_status| {
+                                    let id = comments.len() as u128;
+                                    let sub_comment_with_status =
+                                        old_sub_comment_with_status.map(|old_sub_comment| {
+                                            Comment {
+                                                id,
+                                                parent_id: Some(comment_id),
+                                                depth: 1,
+                                                comment_creator_user_id: old_sub_comment
+                                                    .comment_creator_user_id,
+                                                content: old_sub_comment.content,
+                                                users_who_liked: old_sub_comment.users_who_liked,
+                                                ..Comment::default()
+                                            }
+                                        });
+                                    if let Some(sub_comment) = sub_comment_with_status.inner() {
+                                        likes.push((
+                                            sub_comment.comment_creator_user_id.clone(),
+                                            sub_comment.users_who_liked.len(),
+                                        ));
+                                    }
+                                    comments.push(sub_comment_with_status);
+                                });
+                        });
+                    likes.push((
+                        old_post.creator_user_id.clone(),
+                        old_post.users_who_liked.len(),
+                    ));
+                    // 標籤正規化，不合法的標籤直接拿掉
+                    let mut tags: Vec<String> = Vec::new();
+                    old_post.tags.iter().for_each(|tag| {
+                        if let Ok(tag) = board.normalize_tag(tag) {
+                            if !tags.contains(&tag) {
+                                tags.push(tag);
+                            }
+                        }
+                    });
+                    Post {
+                        id: old_post.id,
+                        title: old_post.title,
+                        content: old_post.content,
+                        tags,
+                        users_who_liked: old_post.users_who_liked,
+                        creator_user_id: old_post.creator_user_id,
+                        comments,
+                        created_at: UNKNOWN_TIMESTAMP,
+                        ..Post::default()
+                    }
+                });
+                // 舊版合約只有作者可以移除文章，留言則不知道是誰移除的（只有管理員可以復原）
+                if let Some(post) = post_with_status.inner_mut() {
+                    post.comments.iter_mut().for_each(|comment_with_status| {
+                        if let Removed(comment) = comment_with_status {
+                            comment.removed_at = Some(UNKNOWN_TIMESTAMP);
+                        }
+                    });
+                }
+                if let Removed(post) = &mut post_with_status {
+                    post.removed_at = Some(UNKNOWN_TIMESTAMP);
+                    post.removed_by = Some(post.creator_user_id.clone());
+                }
+                // 重建作者的文章索引、標籤索引（不包含被移除的文章）與聲望
+                if let Some(post) = post_with_status.inner() {
+                    board.index_author_post(&post.creator_user_id, post_id);
+                }
+                if let Open(post) | Locked(post) = &post_with_status {
+                    board.index_tags(post_id, &post.tags);
+                }
+                likes.into_iter().for_each(|(account_id, count)| {
+                    let karma = board.karma_of(&account_id) + count as u64;
+                    board.set_karma(&account_id, karma);
+                    // 舊的文章沒有發文時間，以轉換的時間當作第一次發文或留言的時間
+                    if board.joined_at.get(&account_id).is_none() {
+                        board.joined_at.insert(&account_id, &env::block_timestamp());
+                    }
+                });
+                board.posts.insert(&post_id, &post_with_status);
+            });
+        board
+    }
+
+    // 新增文章（看板、懸賞、投票等選填設定放在options，null代表都不設定）
+    #[payable]
+    pub fn add_post(
+        &mut self,
+        title: String,
+        content: String,
+        tags: Vec<String>,
+        options: Option<PostOptions>,
+    ) -> WithStatus<Post> {
+        let PostOptions {
+            board,
+            auto_lock_after,
+            references,
+            bounty_deadline,
+            poll,
+            premium_price,
+        } = options.unwrap_or_default();
+        let signer = env::signer_account_id();
+        // 確認懸賞
+        let bounty = match bounty_deadline {
+            Some(deadline) => {
+                if env::attached_deposit() == 0 {
+                    BoardError::InvalidBounty {
+                        reason: "attach a deposit as the bounty",
+                    }
+                    .panic();
+                }
+                if deadline <= env::block_timestamp() {
+                    BoardError::InvalidBounty {
+                        reason: "the deadline must be in the future",
+                    }
+                    .panic();
+                }
+                Some(Bounty {
+                    amount: U128(env::attached_deposit()),
+                    deadline,
+                    accepted_comment_id: None,
+                    settled: false,
+                })
+            }
+            None => {
+                if env::attached_deposit() > 0 {
+                    BoardError::InvalidBounty {
+                        reason: "a deposit is only accepted with a bounty deadline",
+                    }
+                    .panic();
+                }
+                None
+            }
+        };
+        // 確認沒有被封鎖，且有看板的持有資格
+        self.assert_not_banned(board.as_ref());
+        self.assert_board_member(board.as_ref());
+        // 確認沒有超過發文頻率限制
+        self.assert_within_rate_limit(WriteKind::Post);
+        // 確認標題與內文長度
+        self.assert_valid_title(&title);
+        self.assert_valid_content(&content);
+        // 確認投票設定
+        let poll = poll.map(|poll| self.validate_poll(poll));
+        // 付費文章的價格不能是0
+        if premium_price.is_some_and(|price| price.0 == 0) {
+            BoardError::InvalidPremium {
+                reason: "the price must be greater than 0",
+            }
+            .panic();
+        }
+        // 正規化標籤
+        let tags = self
+            .normalize_tags(tags)
+            .unwrap_or_else(|error| error.panic());
+        // 建立新標籤需要足夠的聲望
+        if !self.can_create_tags(&signer, &tags) {
+            self.refund_deposit();
+            return Empty;
+        }
+        // 有指定看板的話，確認可以在看板發文
+        if let Some(board) = &board {
+            if !self.can_post_in_board(&signer, board) {
+                self.refund_deposit();
+                return Empty;
+            }
+        }
+        // 確認引用的對象
+        let references = self.validate_references(references.unwrap_or_default());
+        // 產生新的文章
+        let new_post = Post {
+            id: self.number_of_posts,
+            title,
+            content,
+            tags: tags.clone(),
+            board,
+            auto_lock_after,
+            references,
+            bounty,
+            poll,
+            premium_price,
+            ..Post::default()
+        };
+        // 將新的文章存入
+        self.posts.insert(&new_post.id, &Open(new_post.clone()));
+        // 將請求中的tag存入
+        self.index_tags(new_post.id, &tags);
+        // 記錄作者的文章
+        self.index_author_post(&signer, new_post.id);
+        let target = ContentTarget::Post {
+            post_id: new_post.id,
+        };
+        // 記錄引用
+        self.add_backlinks(&target, &new_post.references);
+        // 通知被提及的帳號（付費文章的內文是加密過的，只看標題）
+        let text = match new_post.premium_price {
+            Some(_) => new_post.title.clone(),
+            None => format!("{}\n{}", new_post.title, new_post.content),
+        };
+        self.notify_mentions(&text, &target, None);
+        // post總數+1
+        self.number_of_posts += 1;
+        // 記錄發文時間
+        self.record_write(WriteKind::Post);
+        // 回傳
+        Open(new_post)
+    }
+
+    // 查詢所有文章
+    pub fn get_all_post(&self) -> Vec<(u128, WithStatus<Post>)> {
+        self.posts
+            .to_vec()
+            .into_iter()
+            // 套用自動鎖定
+            .map(|(id, post_with_status)| (id, self.apply_auto_lock(post_with_status)))
+            // 僅有狀態為開放與鎖定的文章可以被查詢到
+            .filter(|(_, post_with_status)| matches!(post_with_status, Open(_) | Locked(_)))
+            .collect::<Vec<(u128, WithStatus<Post>)>>()
+    }
+
+    // 查詢單篇文章，包含引用這篇文章與其留言的對象
+    pub fn get_post(&self, post_id: u128, with_display_name: Option<bool>) -> WithStatus<Post> {
+        let mut post_with_status = match self.load_post(post_id) {
+            // 僅有狀態為開放與鎖定的文章可以被查詢到
+            Some(post_with_status @ (Open(_) | Locked(_))) => post_with_status,
+            _ => return Empty,
+        };
+        if let Some(post) = post_with_status.inner_mut() {
+            post.referenced_by = self.visible_backlinks(&ContentTarget::Post { post_id });
+            post.comments.iter_mut().for_each(|comment_with_status| {
+                if let Some(comment) = comment_with_status.inner_mut() {
+                    comment.referenced_by = self.visible_backlinks(&ContentTarget::Comment {
+                        post_id,
+                        comment_id: comment.id,
+                    });
+                }
+            });
+        }
+        self.with_display_names(vec![(post_id, post_with_status)], with_display_name)
+            .pop()
+            .map_or(Empty, |(_, post_with_status)| post_with_status)
+    }
+
+    // 透過文字查詢文章
+    pub fn search_post(&self, q: String) -> Vec<(u128, WithStatus<Post>)> {
+        self.posts
+            .to_vec()
+            .into_iter()
+            // 套用自動鎖定
+            .map(|(id, post_with_status)| (id, self.apply_auto_lock(post_with_status)))
+            // 僅有狀態為開放與鎖定的文章可以被查詢到
+            .filter(|(_, post_with_status)| match post_with_status {
+                // 付費文章的內文是加密過的，不列入搜尋
+                Open(post) | Locked(post) => {
+                    post.title.contains(&q)
+                        || (post.premium_price.is_none() && post.content.contains(&q))
+                }
+                _ => false,
+            })
+            .collect::<Vec<(u128, WithStatus<Post>)>>()
+    }
+
+    // 透過標籤查詢文章
+    pub fn search_post_by_tags(&self, tags: Vec<String>) -> Vec<(u128, WithStatus<Post>)> {
+        // 查詢用的標籤也要正規化，才能對應到文章中的標籤
+        let tags = tags
+            .iter()
+            .filter_map(|tag| self.normalize_tag(tag).ok())
+            .collect::<Vec<String>>();
+        self.posts
+            .to_vec()
+            .into_iter()
+            // 套用自動鎖定
+            .map(|(id, post_with_status)| (id, self.apply_auto_lock(post_with_status)))
+            // 僅有狀態為開放與鎖定的文章可以被查詢到
+            .filter(|(_, post_with_status)| match post_with_status {
+                Open(post) | Locked(post) => tags.iter().all(|tag| post.tags.contains(tag)),
+                _ => false,
+            })
+            .collect::<Vec<(u128, WithStatus<Post>)>>()
+    }
+
+    // 透過使用者ID查詢文章
+    pub fn search_post_by_user_id(
+        &self,
+        creator_user_id: AccountId,
+    ) -> Vec<(u128, WithStatus<Post>)> {
+        self.posts
+            .to_vec()
+            .into_iter()
+            // 套用自動鎖定
+            .map(|(id, post_with_status)| (id, self.apply_auto_lock(post_with_status)))
+            // 僅有狀態為開放與鎖定的文章可以被查詢到
+            .filter(|(_, post_with_status)| match post_with_status {
+                Open(post) | Locked(post) => creator_user_id == post.creator_user_id,
+                _ => false,
+            })
+            .collect::<Vec<(u128, WithStatus<Post>)>>()
+    }
+
+    // 查詢所有文章，並填入作者的顯示名稱
+    pub fn get_all_post_with_display_names(&self) -> Vec<(u128, WithStatus<Post>)> {
+        self.with_display_names(self.get_all_post(), Some(true))
+    }
+
+    // 透過文字查詢文章，並填入作者的顯示名稱
+    pub fn search_post_with_display_names(&self, q: String) -> Vec<(u128, WithStatus<Post>)> {
+        self.with_display_names(self.search_post(q), Some(true))
+    }
+
+    // 透過標籤查詢文章，並填入作者的顯示名稱
+    pub fn search_post_by_tags_with_display_names(
+        &self,
+        tags: Vec<String>,
+    ) -> Vec<(u128, WithStatus<Post>)> {
+        self.with_display_names(self.search_post_by_tags(tags), Some(true))
+    }
+
+    // 透過使用者ID查詢文章，並填入作者的顯示名稱
+    pub fn search_post_by_user_id_with_display_names(
+        &self,
+        creator_user_id: AccountId,
+    ) -> Vec<(u128, WithStatus<Post>)> {
+        self.with_display_names(self.search_post_by_user_id(creator_user_id), Some(true))
+    }
+
+    // 追蹤帳號（由呼叫者支付儲存空間費用）
+    #[payable]
+    pub fn follow_account(&mut self, account_id: AccountId) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned(None);
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let mut following = self.following.get(&signer).unwrap_or_default();
+        // 不能追蹤自己，也不能重複追蹤
+        if account_id == signer || following.accounts.contains(&account_id) {
+            self.refund_deposit();
+            return false;
+        }
+        if following.accounts.len() >= self.config.limits.max_following_accounts as usize {
+            BoardError::LimitReached {
+                what: "followed accounts",
+                max: self.config.limits.max_following_accounts,
+            }
+            .panic();
+        }
+        following.accounts.push(account_id.clone());
+        self.following.insert(&signer, &following);
+        let mut followers = self.followers.get(&account_id).unwrap_or_default();
+        followers.push(signer);
+        self.followers.insert(&account_id, &followers);
+        self.charge_storage(initial_storage_usage);
+        true
+    }
+
+    // 取消追蹤帳號
+    pub fn unfollow_account(&mut self, account_id: AccountId) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let mut following = self.following.get(&signer).unwrap_or_default();
+        if !following.accounts.contains(&account_id) {
+            return false;
+        }
+        following
+            .accounts
+            .retain(|followed| *followed != account_id);
+        self.following.insert(&signer, &following);
+        let mut followers = self.followers.get(&account_id).unwrap_or_default();
+        followers.retain(|follower| *follower != signer);
+        self.followers.insert(&account_id, &followers);
+        // 退還追蹤時支付的儲存空間費用
+        self.refund_storage(initial_storage_usage, &signer);
+        true
+    }
+
+    // 追蹤標籤（由呼叫者支付儲存空間費用）
+    #[payable]
+    pub fn follow_tag(&mut self, tag: String) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned(None);
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let tag = self
+            .normalize_tag(&tag)
+            .unwrap_or_else(|error| error.panic());
+        let mut following = self.following.get(&signer).unwrap_or_default();
+        if following.tags.contains(&tag) {
+            self.refund_deposit();
+            return false;
+        }
+        if following.tags.len() >= self.config.limits.max_following_tags as usize {
+            BoardError::LimitReached {
+                what: "followed tags",
+                max: self.config.limits.max_following_tags,
+            }
+            .panic();
+        }
+        following.tags.push(tag);
+        self.following.insert(&signer, &following);
+        self.charge_storage(initial_storage_usage);
+        true
+    }
+
+    // 取消追蹤標籤
+    pub fn unfollow_tag(&mut self, tag: String) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let tag = self
+            .normalize_tag(&tag)
+            .unwrap_or_else(|error| error.panic());
+        let mut following = self.following.get(&signer).unwrap_or_default();
+        if !following.tags.contains(&tag) {
+            return false;
+        }
+        following.tags.retain(|followed| *followed != tag);
+        self.following.insert(&signer, &following);
+        // 退還追蹤時支付的儲存空間費用
+        self.refund_storage(initial_storage_usage, &signer);
+        true
+    }
+
+    // 查詢帳號的追蹤者（分頁）
+    pub fn get_followers(&self, account_id: AccountId, from: u64, limit: u64) -> Vec<AccountId> {
+        self.followers
+            .get(&account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // 查詢帳號追蹤的帳號與標籤
+    pub fn get_following(&self, account_id: AccountId) -> Following {
+        self.following.get(&account_id).unwrap_or_default()
+    }
+
+    // 查詢帳號的動態（追蹤的帳號與標籤的文章，新的在前，每個追蹤的帳號只列出最新的幾篇）
+    // cursor是上一頁最後一篇文章的流水號，只會回傳比它舊的文章（null代表從最新的開始）
+    pub fn get_feed(
+        &self,
+        account_id: AccountId,
+        cursor: Option<u128>,
+        limit: u64,
+    ) -> Vec<(u128, WithStatus<Post>)> {
+        let following = self.following.get(&account_id).unwrap_or_default();
+        // 從作者與標籤的索引找出文章，不需要掃過所有文章
+        let mut post_ids = following
+            .accounts
+            .iter()
+            .flat_map(|followed| self.posts_by_author.get(followed).unwrap_or_default())
+            .chain(
+                following
+                    .tags
+                    .iter()
+                    .flat_map(|tag| self.tags.get(tag).unwrap_or_default()),
+            )
+            .filter(|post_id| *post_id < cursor.unwrap_or(u128::MAX))
+            .collect::<Vec<u128>>();
+        // 流水號越大的文章越新
+        post_ids.sort_unstable_by_key(|post_id| std::cmp::Reverse(*post_id));
+        post_ids.dedup();
+        post_ids
+            .into_iter()
+            .filter_map(|post_id| match self.load_post(post_id) {
+                // 僅有狀態為開放與鎖定的文章可以被查詢到
+                Some(post_with_status @ (Open(_) | Locked(_))) => Some((post_id, post_with_status)),
+                _ => None,
+            })
+            .take(limit as usize)
+            .collect()
+    }
+
+    // 收藏文章（已經收藏過的話會移到新的資料夾，由呼叫者支付儲存空間費用）
+    #[payable]
+    pub fn bookmark_post(&mut self, post_id: u128, folder: Option<String>) -> bool {
+        // 確認沒有被全站封鎖
+        self.assert_not_banned(None);
+        // 僅有狀態為開放與鎖定的文章可以被收藏
+        if !matches!(self.load_post(post_id), Some(Open(_) | Locked(_))) {
+            self.refund_deposit();
+            return false;
+        }
+        // 空白的資料夾名稱視為未分類
+        let folder = folder
+            .map(|folder| folder.trim().to_string())
+            .filter(|folder| !folder.is_empty());
+        if let Some(folder) = &folder {
+            if folder.chars().count() > self.config.limits.max_folder_name_length as usize {
+                BoardError::TooLong {
+                    field: "folder name",
+                    max: self.config.limits.max_folder_name_length,
+                    unit: "characters",
+                }
+                .panic();
+            }
+        }
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let mut bookmarks = self.bookmarks.get(&signer).unwrap_or_default();
+        match bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.post_id == post_id)
+        {
+            // 已經在同一個資料夾
+            Some(bookmark) if bookmark.folder == folder => {
+                self.refund_deposit();
+                return false;
+            }
+            Some(bookmark) => bookmark.folder = folder,
+            None => {
+                if bookmarks.len() >= self.config.limits.max_bookmarks as usize {
+                    BoardError::LimitReached {
+                        what: "bookmarks",
+                        max: self.config.limits.max_bookmarks,
+                    }
+                    .panic();
+                }
+                bookmarks.push(Bookmark {
+                    post_id,
+                    folder,
+                    bookmarked_at: env::block_timestamp(),
+                    removed: false,
+                })
+            }
+        }
+        self.bookmarks.insert(&signer, &bookmarks);
+        self.charge_storage(initial_storage_usage);
+        true
+    }
+
+    // 取消收藏文章
+    pub fn unbookmark_post(&mut self, post_id: u128) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let mut bookmarks = self.bookmarks.get(&signer).unwrap_or_default();
+        let number_of_bookmarks = bookmarks.len();
+        bookmarks.retain(|bookmark| bookmark.post_id != post_id);
+        if bookmarks.len() == number_of_bookmarks {
+            return false;
+        }
+        self.bookmarks.insert(&signer, &bookmarks);
+        // 退還收藏時支付的儲存空間費用
+        self.refund_storage(initial_storage_usage, &signer);
+        true
+    }
+
+    // 查詢帳號收藏的文章（新的在前，分頁，有指定資料夾的話只列出該資料夾），已被移除的文章會標記出來
+    pub fn get_bookmarks(
+        &self,
+        account_id: AccountId,
+        folder: Option<String>,
+        from: u64,
+        limit: u64,
+    ) -> Vec<Bookmark> {
+        self.bookmarks
+            .get(&account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .filter(|bookmark| folder.is_none() || bookmark.folder == folder)
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|mut bookmark| {
+                bookmark.removed =
+                    !matches!(self.load_post(bookmark.post_id), Some(Open(_) | Locked(_)));
+                bookmark
+            })
+            .collect()
+    }
+
+    // 點讚
+    pub fn like_post(&mut self, post_id: u128) -> WithStatus<Post> {
+        self.toggle_post_like(post_id, true)
+    }
+
+    // 取消點讚
+    pub fn unlike_post(&mut self, post_id: u128) -> WithStatus<Post> {
+        self.toggle_post_like(post_id, false)
+    }
+
+    // 文章點讚/取消點讚，並更新作者的聲望
+    fn toggle_post_like(&mut self, post_id: u128, like: bool) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        match self.load_post(post_id) {
+            // 僅有狀態為開放的文章可以被點讚/取消點讚
+            Some(Open(mut post)) => {
+                let signer = env::signer_account_id();
+                // 重複點讚或沒點過讚卻要取消，回傳無
+                if !Self::toggle_like(&mut post.users_who_liked, &signer, like) {
+                    return Empty;
+                }
+                // 儲存改好的文章
+                self.posts.insert(&post_id, &Open(post.clone()));
+                // 幫自己點讚不會增加聲望
+                if post.creator_user_id != signer {
+                    self.change_karma(&post.creator_user_id, like);
+                }
+                // 回傳文章
+                Open(post)
+            }
+            // 找不到文章，回傳無
+            _ => Empty,
+        }
+    }
+
+    // 編輯文章（只有原作者可以修改或移除文章）
+    pub fn edit_post(
+        &mut self,
+        post_id: u128,
+        title: Option<String>,
+        content: Option<String>,
+        tags: Option<Vec<String>>,
+        status: String,
+    ) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        // 有指定的標題與內文要確認長度
+        if let Some(title) = &title {
+            self.assert_valid_title(title);
+        }
+        if let Some(content) = &content {
+            self.assert_valid_content(content);
+        }
+        // 有指定標籤就先正規化
+        let tags = tags.map(|tags| {
+            self.normalize_tags(tags)
+                .unwrap_or_else(|error| error.panic())
+        });
+        // 建立新標籤需要足夠的聲望
+        if let Some(tags) = &tags {
+            if !self.can_create_tags(&env::signer_account_id(), tags) {
+                return Empty;
+            }
+        }
+        // 嘗試找出文章
+        match self.load_post(post_id) {
+            // 找不到，回傳無
+            None => Empty,
+            // 找到，繼續操作
+            Some(original_post_with_status) => {
+                // 判斷找到的文章的狀態，以及確認status是否正確
+                match (
+                    original_post_with_status,
+                    Self::check_status_string(&status),
+                ) {
+                    // 開放，可以做任何操作
+                    (Open(original_post), true) => {
+                        // 確認身份，只有原作者可以修改
+                        if original_post.creator_user_id == env::signer_account_id() {
+                            // 製作新的文章，有指定參數的才改
+                            // 沒有指定參數的欄位（JSON填null），把原本文章的資訊填回去
+                            let original_tags = original_post.tags.clone();
+                            let edited_post_with_status = WithStatus::new_with_status_string(
+                                Post {
+                                    title: title.unwrap_or(original_post.title),
+                                    content: content.unwrap_or(original_post.content),
+                                    tags: tags.unwrap_or(original_post.tags),
+                                    ..original_post
+                                },
+                                status,
+                            );
+                            // 更新標籤索引，移除的文章不列入索引
+                            self.unindex_tags(post_id, &original_tags);
+                            if let Open(post) | Locked(post) = &edited_post_with_status {
+                                self.index_tags(post_id, &post.tags);
+                            }
+                            // 儲存修改過的文章
+                            self.posts.insert(&post_id, &edited_post_with_status);
+                            // 回傳
+                            edited_post_with_status
+                        } else {
+                            // 身份不對，直接回傳無
+                            Empty
+                        }
+                    }
+                    // 鎖定，只能移除
+                    (Locked(original_post), true) => {
+                        // 確認身份，只有原作者可以修改 & 確認是要移除文章
+                        if original_post.creator_user_id == env::signer_account_id()
+                            && status == "Removed"
+                        {
+                            // 移除的文章不列入標籤索引
+                            self.unindex_tags(post_id, &original_post.tags);
+                            let edited_post_with_status =
+                                WithStatus::new_with_status_string(original_post, status);
+                            // 儲存修改過的文章
+                            self.posts.insert(&post_id, &edited_post_with_status);
+                            // 回傳
+                            edited_post_with_status
+                        } else {
+                            // 身份不對或不是要移除文章，直接回傳無
+                            Empty
+                        }
+                    }
+                    // 其他狀態或者status有問題都不能異動
+                    _ => Empty,
+                }
+            }
+        }
+    }
+
+    // 新增留言（parent_id是要回覆的留言，直接回覆文章就是null）
+    pub fn add_comment(
+        &mut self,
+        post_id: u128,
+        parent_id: Option<u128>,
+        content: String,
+        references: Option<Vec<ContentTarget>>,
+    ) -> WithStatus<Post> {
+        // 確認沒有被封鎖，且有看板的持有資格
+        self.assert_not_banned_in_post(post_id);
+        self.assert_board_member_in_post(post_id);
+        // 確認沒有超過留言頻率限制
+        self.assert_within_rate_limit(WriteKind::Comment);
+        // 確認留言長度
+        self.assert_valid_comment(&content, parent_id.is_some());
+        // 只能在開放的文章留言
+        let mut post = match self.load_post(post_id) {
+            Some(Open(post)) => post,
+            _ => return Empty,
+        };
+        // 確認文章的留言數量
+        if post.comments.len() >= self.config.limits.max_comments_per_post as usize {
+            BoardError::TooManyComments {
+                max: self.config.limits.max_comments_per_post,
+            }
+            .panic();
+        }
+        // 回覆留言時，被回覆的留言要是開放的，而且不能超過最大層數
+        let (depth, replied_to) = match parent_id {
+            Some(parent_id) => match post.comment(parent_id) {
+                Some(Open(parent)) => (parent.depth + 1, parent.comment_creator_user_id.clone()),
+                _ => return Empty,
+            },
+            None => (0, post.creator_user_id.clone()),
+        };
+        if depth > self.config.limits.max_reply_depth {
+            BoardError::ReplyTooDeep {
+                max: self.config.limits.max_reply_depth,
+            }
+            .panic();
+        }
+        // 確認引用的對象
+        let references = self.validate_references(references.unwrap_or_default());
+        let comment_id = post.next_comment_id();
+        let target = ContentTarget::Comment {
+            post_id,
+            comment_id,
+        };
+        // 記錄引用
+        self.add_backlinks(&target, &references);
+        // 通知被回覆的文章/留言作者，以及被提及的帳號
+        self.notify(&replied_to, NotificationKind::Reply, &target);
+        self.notify_mentions(&content, &target, Some(&replied_to));
+        post.comments.push(Open(Comment {
+            id: comment_id,
+            parent_id,
+            depth,
+            content,
+            references,
+            ..Comment::default()
+        }));
+        // 儲存更改後的文章
+        self.posts.insert(&post_id, &Open(post.clone()));
+        // 記錄留言時間
+        self.record_write(WriteKind::Comment);
+        // 回傳文章
+        Open(post)
+    }
+
+    // 編輯留言（status是null時修改內容，否則修改狀態）
+    pub fn edit_comment(
+        &mut self,
+        post_id: u128,
+        comment_id: u128,
+        content: String,
+        status: Option<String>,
+    ) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        // 文章鎖定後不能修改其下的留言
+        let mut post = match self.load_post(post_id) {
+            Some(Open(post)) => post,
+            _ => return Empty,
+        };
+        let comment_with_status = match post.comment_mut(comment_id) {
+            Some(comment_with_status) => comment_with_status,
+            None => return Empty,
+        };
+        // 確認身份，只有原作者可以修改
+        match comment_with_status.inner() {
+            Some(comment) if comment.comment_creator_user_id == env::signer_account_id() => {}
+            _ => return Empty,
+        }
+        let is_reply = comment_with_status
+            .inner()
+            .is_some_and(|comment| comment.parent_id.is_some());
+        match (status, &mut *comment_with_status) {
+            // 開放的留言，修改內容
+            (None, Open(comment)) => {
+                self.assert_valid_comment(&content, is_reply);
+                comment.content = content;
+            }
+            // 開放的留言，可以改成任何狀態
+            (Some(status), Open(_)) if Self::check_status_string(&status) => {
+                comment_with_status.set_status(&status);
+            }
+            // 鎖定的留言，只能移除
+            (Some(status), Locked(_)) if status == "Removed" => {
+                comment_with_status.set_status(&status);
+            }
+            // 其他狀態或者status有問題都不能異動
+            _ => return Empty,
+        }
+        // 儲存文章
+        self.posts.insert(&post_id, &Open(post.clone()));
+        // 回傳
+        Open(post)
+    }
+
+    // 查詢文章或留言底下的直接回覆（分頁，parent_id是null時查詢直接回覆文章的留言）
+    pub fn get_replies(
+        &self,
+        post_id: u128,
+        parent_id: Option<u128>,
+        from: u64,
+        limit: u64,
+    ) -> Vec<WithStatus<Comment>> {
+        match self.load_post(post_id) {
+            // 僅有狀態為開放與鎖定的文章與留言可以被查詢到
+            Some(Open(post)) | Some(Locked(post)) => post
+                .comments
+                .into_iter()
+                .filter(|comment_with_status| match comment_with_status {
+                    Open(comment) | Locked(comment) => comment.parent_id == parent_id,
+                    _ => false,
+                })
+                .skip(from as usize)
+                .take(limit as usize)
+                .collect(),
+            _ => Vec::default(),
+        }
+    }
+
+    // 依討論串順序（每則留言後面接著它的回覆）查詢文章的留言（分頁）
+    pub fn get_thread(&self, post_id: u128, from: u64, limit: u64) -> Vec<WithStatus<Comment>> {
+        let post = match self.load_post(post_id) {
+            Some(Open(post)) | Some(Locked(post)) => post,
+            _ => return Vec::default(),
+        };
+        // 留言流水號對應到的位置
+        let positions: HashMap<u128, usize> = post
+            .comments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, comment_with_status)| {
+                comment_with_status
+                    .inner()
+                    .map(|comment| (comment.id, index))
+            })
+            .collect();
+        // 整理出每則留言的回覆
+        let mut roots: Vec<usize> = Vec::new();
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); post.comments.len()];
+        post.comments
+            .iter()
+            .enumerate()
+            .for_each(|(index, comment_with_status)| {
+                match comment_with_status
+                    .inner()
+                    .and_then(|comment| comment.parent_id)
+                    .and_then(|parent_id| positions.get(&parent_id))
+                {
+                    Some(&parent_index) => children[parent_index].push(index),
+                    None => roots.push(index),
+                }
+            });
+        // 深度優先走訪，被移除的留言不顯示，但它底下的回覆仍然會顯示
+        let mut order: Vec<usize> = Vec::new();
+        let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+        while let Some(index) = stack.pop() {
+            order.push(index);
+            stack.extend(children[index].iter().rev());
+        }
+        let mut comments: Vec<Option<WithStatus<Comment>>> =
+            post.comments.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .filter_map(|index| comments[index].take())
+            .filter(|comment_with_status| matches!(comment_with_status, Open(_) | Locked(_)))
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // 查詢帳號的通知（新的在前，分頁）
+    pub fn get_notifications(
+        &self,
+        account_id: AccountId,
+        from: u64,
+        limit: u64,
+    ) -> Vec<Notification> {
+        let inbox = self.inboxes.get(&account_id).unwrap_or_default();
+        inbox
+            .notifications
+            .into_iter()
+            .rev()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|mut notification| {
+                notification.read = notification.id <= inbox.read_up_to;
+                notification
+            })
+            .collect()
+    }
+
+    // 將自己流水號up_to（含）以前的通知標記為已讀
+    pub fn mark_notifications_read(&mut self, up_to: u64) -> bool {
+        let signer = env::signer_account_id();
+        let mut inbox = match self.inboxes.get(&signer) {
+            Some(inbox) => inbox,
+            None => return false,
+        };
+        // 不能標記還沒收到的通知，也不能把已讀改回未讀
+        let up_to = up_to.min(inbox.next_id);
+        if up_to <= inbox.read_up_to {
+            return false;
+        }
+        inbox.read_up_to = up_to;
+        self.inboxes.insert(&signer, &inbox);
+        true
+    }
+
+    // 查詢標籤與各標籤的文章數（分頁）
+    pub fn get_tags(&self, from: u64, limit: u64) -> Vec<(String, u64)> {
+        self.tags
+            .iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .map(|(tag, posts_id_vec)| (tag, posts_id_vec.len() as u64))
+            .collect::<Vec<(String, u64)>>()
+    }
+
+    // 查詢熱門標籤（依文章數由多到少排序）
+    pub fn get_popular_tags(&self, limit: u64) -> Vec<(String, u64)> {
+        let mut tags = self
+            .tags
+            .iter()
+            .map(|(tag, posts_id_vec)| (tag, posts_id_vec.len() as u64))
+            .collect::<Vec<(String, u64)>>();
+        // 文章數相同時依標籤名稱排序，讓結果固定
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tags.truncate(limit as usize);
+        tags
+    }
+
+    // 重新命名標籤（僅限管理員）
+    pub fn rename_tag(&mut self, from: String, to: String) -> bool {
+        self.merge_tags(vec![from], to)
+    }
+
+    // 將多個標籤合併成一個標籤（僅限管理員）
+    pub fn merge_tags(&mut self, sources: Vec<String>, target: String) -> bool {
+        // 確認身份
+        if !self.is_moderator(&env::signer_account_id()) {
+            return false;
+        }
+        // 正規化所有標籤，不合法就不做任何事
+        let target = match self.normalize_tag(&target) {
+            Ok(target) => target,
+            Err(_) => return false,
+        };
+        let sources = match sources
+            .iter()
+            .map(|source| self.normalize_tag(source))
+            .collect::<Result<Vec<String>, BoardError>>()
+        {
+            Ok(sources) => sources,
+            Err(_) => return false,
+        };
+        // 自己合併到自己不需要處理
+        for source in sources.into_iter().filter(|source| *source != target) {
+            // 把舊標籤從索引中拿掉
+            self.tags.remove(&source);
+            // 修改所有使用舊標籤的文章，被移除的文章不在索引中也要改，否則復原時會把舊標籤帶回來
+            let posts_with_source = self
+                .posts
+                .iter()
+                .filter(|(_, post_with_status)| {
+                    post_with_status
+                        .inner()
+                        .is_some_and(|post| post.tags.contains(&source))
+                })
+                .collect::<Vec<(u128, WithStatus<Post>)>>();
+            for (post_id, mut post_with_status) in posts_with_source {
+                if let Some(post) = post_with_status.inner_mut() {
+                    // 新標籤已經存在就直接拿掉舊標籤，否則把舊標籤換成新標籤
+                    if post.tags.contains(&target) {
+                        post.tags.retain(|tag| *tag != source);
+                    } else {
+                        post.tags.iter_mut().for_each(|tag| {
+                            if *tag == source {
+                                *tag = target.clone();
+                            }
+                        });
+                    }
+                }
+                self.posts.insert(&post_id, &post_with_status);
+                // 被移除的文章不加入索引
+                if !matches!(post_with_status, Removed(_)) {
+                    self.index_tags(post_id, std::slice::from_ref(&target));
+                }
+            }
+        }
+        true
+    }
+
+    // 新增管理員（僅限合約擁有者）
+    pub fn add_moderator(&mut self, account_id: AccountId) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.internal_add_moderator(&account_id)
+    }
+
+    // 移除管理員（僅限合約擁有者）
+    pub fn remove_moderator(&mut self, account_id: AccountId) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.internal_remove_moderator(&account_id)
+    }
+
+    // 查詢所有管理員
+    pub fn get_moderators(&self) -> Vec<AccountId> {
+        self.moderators.to_vec()
+    }
+
+    // 設定個人資料（儲存費用由呼叫者支付）
+    #[payable]
+    pub fn set_profile(
+        &mut self,
+        display_name: Option<String>,
+        bio: Option<String>,
+        avatar: Option<String>,
+        signature: Option<String>,
+        public_key: Option<String>,
+    ) -> Profile {
+        // 確認沒有被全站封鎖
+        self.assert_not_banned(None);
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::signer_account_id();
+        // 沒有指定參數的欄位（JSON填null），把原本的資料填回去
+        let original_profile = self.profiles.get(&account_id).unwrap_or_default();
+        let profile = Profile {
+            display_name: display_name.unwrap_or(original_profile.display_name),
+            bio: bio.unwrap_or(original_profile.bio),
+            avatar: avatar.unwrap_or(original_profile.avatar),
+            signature: signature.unwrap_or(original_profile.signature),
+        };
+        // 儲存個人資料，公鑰另外存，個人資料的格式才能與舊版相容
+        self.profiles.insert(&account_id, &profile);
+        if let Some(public_key) = public_key {
+            if public_key.is_empty() {
+                self.public_keys.remove(&account_id);
+            } else {
+                self.public_keys.insert(&account_id, &public_key);
+            }
+        }
+        // 收取儲存費用
+        self.charge_storage(initial_storage_usage);
+        // 回傳
+        profile
+    }
+
+    // 查詢個人資料，以及文章、留言與收到的讚數
+    pub fn get_profile(&self, account_id: AccountId) -> ProfileView {
+        let mut post_count = 0;
+        let mut comment_count = 0;
+        let mut like_count = 0;
+        // 僅計算狀態為開放與鎖定的文章與留言
+        self.posts.iter().for_each(|(_, post_with_status)| {
+            if let Open(post) | Locked(post) = post_with_status {
+                if post.creator_user_id == account_id {
+                    post_count += 1;
+                    like_count += post.users_who_liked.len() as u64;
+                }
+                post.comments.iter().for_each(|comment_with_status| {
+                    if let Open(comment) | Locked(comment) = comment_with_status {
+                        if comment.comment_creator_user_id == account_id {
+                            comment_count += 1;
+                            like_count += comment.users_who_liked.len() as u64;
+                        }
+                    }
+                });
+            }
+        });
+        ProfileView {
+            profile: self.profiles.get(&account_id),
+            public_key: self.public_keys.get(&account_id),
+            account_id,
+            post_count,
+            comment_count,
+            like_count,
+        }
+    }
+
+    // 留言點讚
+    pub fn like_comment(&mut self, post_id: u128, comment_id: u128) -> WithStatus<Post> {
+        self.toggle_comment_like(post_id, comment_id, true)
+    }
+
+    // 留言取消點讚
+    pub fn unlike_comment(&mut self, post_id: u128, comment_id: u128) -> WithStatus<Post> {
+        self.toggle_comment_like(post_id, comment_id, false)
+    }
+
+    // 留言點讚/取消點讚，並更新留言者的聲望
+    fn toggle_comment_like(
+        &mut self,
+        post_id: u128,
+        comment_id: u128,
+        like: bool,
+    ) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        let signer = env::signer_account_id();
+        // 僅有開放的文章底下的留言可以被點讚
+        let mut post = match self.load_post(post_id) {
+            Some(Open(post)) => post,
+            _ => return Empty,
+        };
+        // 留言必須存在且是開放狀態
+        let comment = match post.comment_mut(comment_id) {
+            Some(Open(comment)) => comment,
+            _ => return Empty,
+        };
+        let creator_user_id = comment.comment_creator_user_id.clone();
+        // 重複點讚或沒點過讚卻要取消，回傳無
+        if !Self::toggle_like(&mut comment.users_who_liked, &signer, like) {
+            return Empty;
+        }
+        // 儲存文章
+        self.posts.insert(&post_id, &Open(post.clone()));
+        // 幫自己點讚不會增加聲望
+        if creator_user_id != signer {
+            self.change_karma(&creator_user_id, like);
+        }
+        // 回傳
+        Open(post)
+    }
+
+    // 文章推文（reaction填null代表收回推文）
+    pub fn react_post(&mut self, post_id: u128, reaction: Option<Reaction>) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        match self.load_post(post_id) {
+            // 僅有狀態為開放的文章可以推文
+            Some(Open(mut post)) => {
+                if !Self::set_reaction(
+                    &mut post.reactions,
+                    &mut post.reaction_tally,
+                    &env::signer_account_id(),
+                    reaction,
+                ) {
+                    return Empty;
+                }
+                // 儲存文章
+                self.posts.insert(&post_id, &Open(post.clone()));
+                // 回傳
+                Open(post)
+            }
+            // 找不到文章，回傳無
+            _ => Empty,
+        }
+    }
+
+    // 留言推文（reaction填null代表收回推文）
+    pub fn react_comment(
+        &mut self,
+        post_id: u128,
+        comment_id: u128,
+        reaction: Option<Reaction>,
+    ) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        // 僅有開放的文章底下的開放留言可以推文
+        let mut post = match self.load_post(post_id) {
+            Some(Open(post)) => post,
+            _ => return Empty,
+        };
+        let comment = match post.comment_mut(comment_id) {
+            Some(Open(comment)) => comment,
+            _ => return Empty,
+        };
+        if !Self::set_reaction(
+            &mut comment.reactions,
+            &mut comment.reaction_tally,
+            &env::signer_account_id(),
+            reaction,
+        ) {
+            return Empty;
+        }
+        // 儲存文章
+        self.posts.insert(&post_id, &Open(post.clone()));
+        // 回傳
+        Open(post)
+    }
+
+    // 查詢文章摘要（分頁），包含淨推文數與「爆」標記
+    pub fn get_post_summaries(&self, from: u64, limit: u64) -> Vec<PostSummary> {
+        self.posts
+            .iter()
+            // 僅有狀態為開放與鎖定的文章可以被查詢到
+            .filter_map(
+                |(_, post_with_status)| match self.apply_auto_lock(post_with_status) {
+                    Open(post) | Locked(post) => Some(PostSummary::from(&post)),
+                    _ => None,
+                },
+            )
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect::<Vec<PostSummary>>()
+    }
+
+    // 對文章或留言加上表情符號
+    pub fn add_emoji_reaction(&mut self, target: ContentTarget, emoji: String) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(target.post_id());
+        // 只能使用允許的表情符號，且僅有開放的對象可以加上表情符號
+        if !self.config.allowed_emojis.contains(&emoji)
+            || !matches!(self.target_author(&target), Open(_))
+        {
+            return false;
+        }
+        let key = (target, env::signer_account_id());
+        let mut my_emojis = self.emoji_reactions_by_user.get(&key).unwrap_or_default();
+        // 同一個表情符號只能加一次
+        if my_emojis.contains(&emoji) {
+            return false;
+        }
+        my_emojis.push(emoji.clone());
+        self.emoji_reactions_by_user.insert(&key, &my_emojis);
+        // 更新計數
+        let mut emoji_counts = self.emoji_counts.get(&key.0).unwrap_or_default();
+        match emoji_counts.iter_mut().find(|(e, _)| *e == emoji) {
+            Some((_, count)) => *count += 1,
+            None => emoji_counts.push((emoji, 1)),
+        }
+        self.emoji_counts.insert(&key.0, &emoji_counts);
+        true
+    }
+
+    // 移除自己加上的表情符號
+    pub fn remove_emoji_reaction(&mut self, target: ContentTarget, emoji: String) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(target.post_id());
+        // 僅有開放的對象可以移除表情符號
+        if !matches!(self.target_author(&target), Open(_)) {
+            return false;
+        }
+        let key = (target, env::signer_account_id());
+        let mut my_emojis = self.emoji_reactions_by_user.get(&key).unwrap_or_default();
+        // 沒加過這個表情符號
+        let index = match my_emojis.iter().position(|e| *e == emoji) {
+            Some(index) => index,
+            None => return false,
+        };
+        my_emojis.remove(index);
+        if my_emojis.is_empty() {
+            self.emoji_reactions_by_user.remove(&key);
+        } else {
+            self.emoji_reactions_by_user.insert(&key, &my_emojis);
+        }
+        // 更新計數，歸零的表情符號一併移除
+        let mut emoji_counts = self.emoji_counts.get(&key.0).unwrap_or_default();
+        emoji_counts.iter_mut().for_each(|(e, count)| {
+            if *e == emoji {
+                *count -= 1;
+            }
+        });
+        emoji_counts.retain(|(_, count)| *count > 0);
+        if emoji_counts.is_empty() {
+            self.emoji_counts.remove(&key.0);
+        } else {
+            self.emoji_counts.insert(&key.0, &emoji_counts);
+        }
+        true
+    }
+
+    // 查詢對象的表情符號計數
+    pub fn get_reactions(&self, target: ContentTarget) -> Vec<(String, u64)> {
+        // 僅有開放與鎖定的對象可以被查詢到
+        match self.target_author(&target) {
+            Open(_) | Locked(_) => self.emoji_counts.get(&target).unwrap_or_default(),
+            _ => Vec::default(),
+        }
+    }
+
+    // 查詢帳號在對象上使用的表情符號
+    pub fn get_my_reactions(&self, target: ContentTarget, account_id: AccountId) -> Vec<String> {
+        match self.target_author(&target) {
+            Open(_) | Locked(_) => self
+                .emoji_reactions_by_user
+                .get(&(target, account_id))
+                .unwrap_or_default(),
+            _ => Vec::default(),
+        }
+    }
+
+    // 設定可以使用的表情符號（僅限合約擁有者）
+    pub fn set_allowed_emojis(&mut self, allowed_emojis: Vec<String>) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.allowed_emojis = allowed_emojis;
+        true
+    }
+
+    // 檢舉文章或留言（每個帳號對同一個對象只能檢舉一次）
+    pub fn report(&mut self, target: ContentTarget, reason: String) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(target.post_id());
+        // 檢舉理由存在合約中，要限制長度
+        if reason.chars().count() > self.config.limits.max_report_reason_length as usize {
+            BoardError::TooLong {
+                field: "report reason",
+                max: self.config.limits.max_report_reason_length,
+                unit: "characters",
+            }
+            .panic();
+        }
+        // 僅有開放與鎖定的對象可以被檢舉
+        if !matches!(self.target_author(&target), Open(_) | Locked(_)) {
+            return false;
+        }
+        let reporter = env::signer_account_id();
+        let mut report_case = self.reports.get(&target).unwrap_or(ReportCase {
+            target: target.clone(),
+            reports: Vec::default(),
+            auto_hidden: false,
+            status_before_hide: None,
+        });
+        // 已經檢舉過了
+        if report_case
+            .reports
+            .iter()
+            .any(|report| report.reporter == reporter)
+        {
+            return false;
+        }
+        report_case.reports.push(Report {
+            reporter,
+            reason,
+            reported_at: env::block_timestamp(),
+        });
+        // 檢舉數達到門檻，自動隱藏
+        if let Some(threshold) = self.config.auto_hide_report_threshold {
+            if report_case.reports.len() as u64 >= threshold && !report_case.auto_hidden {
+                report_case.status_before_hide = self.target_status(&target);
+                report_case.auto_hidden = self.set_target_status(&target, "Removed");
+                // 自動隱藏不是檢舉者移除的，不記錄移除者（只有管理員可以復原）
+                if report_case.auto_hidden {
+                    self.clear_removed_by(&target);
+                }
+            }
+        }
+        self.reports.insert(&target, &report_case);
+        true
+    }
+
+    // 查詢待處理的檢舉案件（依檢舉數由多到少排序）
+    pub fn get_moderation_queue(&self, from: u64, limit: u64) -> Vec<ReportCase> {
+        let mut report_cases = self.reports.values().collect::<Vec<ReportCase>>();
+        report_cases.sort_by_key(|report_case| std::cmp::Reverse(report_case.reports.len()));
+        report_cases
+            .into_iter()
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect::<Vec<ReportCase>>()
+    }
+
+    // 處理檢舉案件（僅限管理員）
+    pub fn resolve_report(&mut self, target: ContentTarget, action: ModerationAction) -> bool {
+        // 確認身份
+        if !self.is_moderator(&env::signer_account_id()) {
+            return false;
+        }
+        // 找不到檢舉案件
+        let report_case = match self.reports.get(&target) {
+            Some(report_case) => report_case,
+            None => return false,
+        };
+        match action {
+            // 駁回，被自動隱藏的對象恢復成隱藏前的狀態
+            ModerationAction::Dismiss => {
+                if report_case.auto_hidden {
+                    let status = report_case
+                        .status_before_hide
+                        .unwrap_or_else(|| "Open".to_string());
+                    self.set_target_status(&target, &status);
+                }
+            }
+            // 鎖定
+            ModerationAction::Lock => {
+                self.set_target_status(&target, "Locked");
+            }
+            // 移除
+            ModerationAction::Remove => {
+                self.set_target_status(&target, "Removed");
+            }
+        }
+        // 處理完畢，從待處理列表移除
+        self.reports.remove(&target);
+        true
+    }
+
+    // 設定自動隱藏的檢舉數門檻（僅限合約擁有者，null代表不自動隱藏）
+    pub fn set_auto_hide_report_threshold(&mut self, threshold: Option<u64>) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.auto_hide_report_threshold = threshold;
+        true
+    }
+
+    // 查詢帳號的聲望
+    pub fn get_karma(&self, account_id: AccountId) -> u64 {
+        self.karma_of(&account_id)
+    }
+
+    // 聲望排行榜（由高到低）
+    pub fn get_karma_leaderboard(&self, limit: u64) -> Vec<(AccountId, u64)> {
+        let mut leaderboard = self
+            .karma
+            .iter()
+            .filter(|(_, karma)| *karma > 0)
+            .collect::<Vec<(AccountId, u64)>>();
+        leaderboard.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        leaderboard.truncate(limit as usize);
+        leaderboard
+    }
+
+    // 設定各項權限所需的聲望（僅限合約擁有者）
+    pub fn set_karma_thresholds(&mut self, karma_thresholds: KarmaThresholds) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.karma_thresholds = karma_thresholds;
+        true
+    }
+
+    // 查詢合約設定
+    pub fn get_config(&self) -> Config {
+        self.config.clone()
+    }
+
+    // 新增看板（僅限管理員）
+    pub fn create_board(
+        &mut self,
+        name: String,
+        description: String,
+        restricted: bool,
+        auto_lock_after: Option<u64>,
+    ) -> bool {
+        // 確認身份
+        if !self.is_moderator(&env::signer_account_id()) {
+            return false;
+        }
+        self.internal_create_board(name, description, restricted, auto_lock_after)
+    }
+
+    // 查詢所有看板
+    pub fn get_boards(&self) -> Vec<Board> {
+        self.boards.values().collect::<Vec<Board>>()
+    }
+
+    // 封鎖帳號（僅限管理員，board填null代表全站封鎖，同一個看板的封鎖會被覆蓋）
+    pub fn ban_account(
+        &mut self,
+        account_id: AccountId,
+        board: Option<String>,
+        reason: String,
+        expires_at: Option<u64>,
+    ) -> bool {
+        let signer = env::signer_account_id();
+        // 確認身份，且不能封鎖合約擁有者
+        if !self.is_moderator(&signer) || account_id == self.owner_id {
+            return false;
+        }
+        let mut bans = self.bans.get(&account_id).unwrap_or_default();
+        bans.retain(|ban| ban.board != board && ban.is_active());
+        bans.push(Ban {
+            account_id: account_id.clone(),
+            board,
+            reason,
+            expires_at,
+            banned_by: signer,
+            banned_at: env::block_timestamp(),
+        });
+        self.bans.insert(&account_id, &bans);
+        true
+    }
+
+    // 解除封鎖（僅限管理員）
+    pub fn unban_account(&mut self, account_id: AccountId, board: Option<String>) -> bool {
+        if !self.is_moderator(&env::signer_account_id()) {
+            return false;
+        }
+        let mut bans = self.bans.get(&account_id).unwrap_or_default();
+        let original_length = bans.len();
+        bans.retain(|ban| ban.board != board);
+        if bans.is_empty() {
+            self.bans.remove(&account_id);
+        } else {
+            self.bans.insert(&account_id, &bans);
+        }
+        bans.len() != original_length
+    }
+
+    // 查詢所有有效的封鎖（分頁）
+    pub fn get_bans(&self, from: u64, limit: u64) -> Vec<Ban> {
+        self.bans
+            .values()
+            .flatten()
+            .filter(|ban| ban.is_active())
+            .skip(from as usize)
+            .take(limit as usize)
+            .collect::<Vec<Ban>>()
+    }
+
+    // 查詢帳號是否在看板（或全站）被封鎖
+    pub fn is_banned(&self, account_id: AccountId, board: Option<String>) -> bool {
+        self.active_ban(&account_id, board.as_ref()).is_some()
+    }
+
+    // 設定發文/留言頻率限制（僅限合約擁有者）
+    pub fn set_rate_limits(&mut self, rate_limits: RateLimits) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.rate_limits = rate_limits;
+        true
+    }
+
+    // 讓帳號不受頻率限制（僅限合約擁有者）
+    pub fn add_rate_limit_exemption(&mut self, account_id: AccountId) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.rate_limit_exempt.insert(&account_id)
+    }
+
+    // 取消帳號的頻率限制豁免（僅限合約擁有者）
+    pub fn remove_rate_limit_exemption(&mut self, account_id: AccountId) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.rate_limit_exempt.remove(&account_id)
+    }
+
+    // 查詢不受頻率限制的帳號
+    pub fn get_rate_limit_exemptions(&self) -> Vec<AccountId> {
+        self.rate_limit_exempt.to_vec()
+    }
+
+    // 設定內容長度與數量限制（僅限合約擁有者）
+    pub fn set_limits(&mut self, limits: ContentLimits) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.internal_set_limits(limits)
+    }
+
+    // 復原移除的文章（原作者或管理員，且在復原期限內）
+    pub fn restore_post(&mut self, post_id: u128) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        match self.posts.get(&post_id) {
+            // 只有被移除的文章可以復原
+            Some(Removed(post)) => {
+                if !self.can_restore(
+                    &env::signer_account_id(),
+                    &post.creator_user_id,
+                    post.removed_at,
+                    post.removed_by.as_ref(),
+                ) {
+                    return Empty;
+                }
+                // 恢復開放，並重新加入標籤索引
+                self.set_target_status(&ContentTarget::Post { post_id }, "Open");
+                self.load_post(post_id).unwrap_or(Empty)
+            }
+            // 不是被移除的文章，回傳無
+            _ => Empty,
+        }
+    }
+
+    // 復原移除的留言（原作者或管理員，且在復原期限內）
+    pub fn restore_comment(&mut self, post_id: u128, comment_id: u128) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        // 文章被移除時，不能復原底下的留言
+        let post = match self.load_post(post_id) {
+            Some(Open(post)) | Some(Locked(post)) => post,
+            _ => return Empty,
+        };
+        // 只有被移除的留言可以復原
+        let comment = match post.comment(comment_id) {
+            Some(Removed(comment)) => comment,
+            _ => return Empty,
+        };
+        if !self.can_restore(
+            &env::signer_account_id(),
+            &comment.comment_creator_user_id,
+            comment.removed_at,
+            comment.removed_by.as_ref(),
+        ) {
+            return Empty;
+        }
+        // 恢復開放
+        self.set_target_status(
+            &ContentTarget::Comment {
+                post_id,
+                comment_id,
+            },
+            "Open",
+        );
+        self.load_post(post_id).unwrap_or(Empty)
+    }
+
+    // 查詢帳號被移除的文章與留言（不包含標題與內容）
+    pub fn get_my_removed(&self, account_id: AccountId) -> Vec<RemovedItem> {
+        self.removed_items(&account_id, false)
+    }
+
+    // 查詢自己被移除的文章與留言，包含標題與內容
+    // view呼叫沒有簽署者，無法確認是本人，所以必須透過交易呼叫
+    pub fn get_my_removed_with_content(&mut self) -> Vec<RemovedItem> {
+        self.removed_items(&env::signer_account_id(), true)
+    }
+
+    // 列出帳號被移除的文章與留言（full為true時包含標題與內容）
+    fn removed_items(&self, account_id: &AccountId, full: bool) -> Vec<RemovedItem> {
+        let grace_period = self.config.restore_grace_period;
+        let removed_item = |target: ContentTarget,
+                            title: Option<String>,
+                            content: String,
+                            removed_at: Option<u64>,
+                            removed_by: Option<AccountId>| {
+            let removed_at = removed_at.unwrap_or(UNKNOWN_TIMESTAMP);
+            RemovedItem {
+                target,
+                title: title.filter(|_| full),
+                content: Some(content).filter(|_| full),
+                removed_at,
+                removed_by,
+                // 舊版合約移除的內容沒有復原期限
+                restorable_until: if removed_at == UNKNOWN_TIMESTAMP {
+                    u64::MAX
+                } else {
+                    removed_at.saturating_add(grace_period)
+                },
+            }
+        };
+        let mut removed_items: Vec<RemovedItem> = Vec::new();
+        self.posts.iter().for_each(|(post_id, post_with_status)| {
+            // 被移除的文章
+            if let Removed(post) = &post_with_status {
+                if post.creator_user_id == *account_id {
+                    removed_items.push(removed_item(
+                        ContentTarget::Post { post_id },
+                        Some(post.title.clone()),
+                        post.content.clone(),
+                        post.removed_at,
+                        post.removed_by.clone(),
+                    ));
+                }
+            }
+            let post = match post_with_status.into_inner() {
+                Some(post) => post,
+                None => return,
+            };
+            // 被移除的留言
+            post.comments.into_iter().for_each(|comment_with_status| {
+                if let Removed(comment) = comment_with_status {
+                    if comment.comment_creator_user_id == *account_id {
+                        removed_items.push(removed_item(
+                            ContentTarget::Comment {
+                                post_id,
+                                comment_id: comment.id,
+                            },
+                            None,
+                            comment.content,
+                            comment.removed_at,
+                            comment.removed_by,
+                        ));
+                    }
+                }
+            });
+        });
+        removed_items
+    }
+
+    // 設定移除後可以復原的期間（僅限合約擁有者）
+    pub fn set_restore_grace_period(&mut self, restore_grace_period: u64) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.restore_grace_period = restore_grace_period;
+        true
+    }
+
+    // 解除文章鎖定（原作者或管理員），自動鎖定會從解除的時間重新計算
+    pub fn unlock_post(&mut self, post_id: u128) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        match self.load_post(post_id) {
+            // 只有鎖定的文章可以解除鎖定
+            Some(Locked(mut post)) => {
+                let signer = env::signer_account_id();
+                // 確認身份
+                if post.creator_user_id != signer && !self.is_moderator(&signer) {
+                    return Empty;
+                }
+                post.unlocked_at = Some(env::block_timestamp());
+                // 儲存文章
+                self.posts.insert(&post_id, &Open(post.clone()));
+                // 回傳
+                Open(post)
+            }
+            // 不是鎖定的文章，回傳無
+            _ => Empty,
+        }
+    }
+
+    // 解除留言鎖定（原作者或管理員）
+    pub fn unlock_comment(&mut self, post_id: u128, comment_id: u128) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        // 文章鎖定時不能解除底下的留言
+        let post = match self.load_post(post_id) {
+            Some(Open(post)) => post,
+            _ => return Empty,
+        };
+        // 只有鎖定的留言可以解除鎖定
+        let author = match post.comment(comment_id) {
+            Some(Locked(comment)) => comment.comment_creator_user_id.clone(),
+            _ => return Empty,
+        };
+        let signer = env::signer_account_id();
+        // 確認身份
+        if author != signer && !self.is_moderator(&signer) {
+            return Empty;
+        }
+        // 恢復開放
+        self.set_target_status(
+            &ContentTarget::Comment {
+                post_id,
+                comment_id,
+            },
+            "Open",
+        );
+        self.load_post(post_id).unwrap_or(Empty)
+    }
+
+    // 設定看板的自動鎖定期限（僅限管理員，null代表不自動鎖定）
+    pub fn set_board_auto_lock(&mut self, name: String, auto_lock_after: Option<u64>) -> bool {
+        if !self.is_moderator(&env::signer_account_id()) {
+            return false;
+        }
+        match self.boards.get(&name) {
+            Some(mut board) => {
+                board.auto_lock_after = auto_lock_after;
+                self.boards.insert(&name, &board);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 打賞文章作者（附加的押金扣除平台抽成後轉給作者）
+    #[payable]
+    pub fn tip_post(&mut self, post_id: u128) -> Promise {
+        self.send_tip(ContentTarget::Post { post_id })
+    }
+
+    // 打賞留言作者（附加的押金扣除平台抽成後轉給留言者）
+    #[payable]
+    pub fn tip_comment(&mut self, post_id: u128, comment_id: u128) -> Promise {
+        self.send_tip(ContentTarget::Comment {
+            post_id,
+            comment_id,
+        })
+    }
+
+    // 打賞轉帳完成後記錄打賞，轉帳失敗的話把押金（包含平台抽成）退回給打賞者
+    #[private]
+    pub fn on_tip_transferred(
+        &mut self,
+        target: ContentTarget,
+        tipper: AccountId,
+        author: AccountId,
+        amount: U128,
+        fee: U128,
+    ) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                let tips = self.tips.get(&target).unwrap_or(0);
+                self.tips.insert(&target, &(tips + amount.0));
+                let tips_by_author = self.tips_by_author.get(&author).unwrap_or(0);
+                self.tips_by_author
+                    .insert(&author, &(tips_by_author + amount.0));
+                self.collected_fees += fee.0;
+                true
+            }
+            _ => {
+                Promise::new(tipper).transfer(amount.0 + fee.0);
+                false
+            }
+        }
+    }
+
+    // 查詢文章或留言收到的打賞總額
+    pub fn get_tips(&self, target: ContentTarget) -> U128 {
+        U128(self.tips.get(&target).unwrap_or(0))
+    }
+
+    // 查詢作者收到的打賞總額
+    pub fn get_author_tips(&self, account_id: AccountId) -> U128 {
+        U128(self.tips_by_author.get(&account_id).unwrap_or(0))
+    }
+
+    // 查詢還沒提領的平台抽成
+    pub fn get_collected_fees(&self) -> U128 {
+        U128(self.collected_fees)
+    }
+
+    // 提領平台抽成給合約擁有者（僅限合約擁有者，amount是null時全部提領）
+    #[payable]
+    pub fn withdraw_fees(&mut self, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        if env::signer_account_id() != self.owner_id {
+            BoardError::OwnerOnly {
+                action: "withdraw fees",
+            }
+            .panic();
+        }
+        let amount = amount.map_or(self.collected_fees, |amount| amount.0);
+        if amount == 0 || amount > self.collected_fees {
+            BoardError::InvalidWithdrawal {
+                amount,
+                available: self.collected_fees,
+            }
+            .panic();
+        }
+        // 先扣除，轉帳失敗時在callback加回來
+        self.collected_fees -= amount;
+        Promise::new(self.owner_id.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(TIP_CALLBACK_GAS)
+                .on_fees_withdrawn(U128(amount)),
+        )
+    }
+
+    // 轉帳失敗的話把平台抽成加回來
+    #[private]
+    pub fn on_fees_withdrawn(&mut self, amount: U128) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                self.collected_fees += amount.0;
+                false
+            }
+        }
+    }
+
+    // 設定打賞的平台抽成（萬分比，僅限合約擁有者）
+    pub fn set_tip_fee(&mut self, tip_fee_basis_points: u16) -> bool {
+        if env::signer_account_id() != self.owner_id || tip_fee_basis_points > 10_000 {
+            return false;
+        }
+        self.config.tip_fee_basis_points = tip_fee_basis_points;
+        true
+    }
+
+    // NEP-141代幣打賞：使用者以ft_transfer_call把代幣轉給合約，msg是要打賞的對象（ContentTarget的JSON）
+    // 代幣會記入作者的餘額，回傳值是要退還給使用者的數量（不合法的打賞會全數退還）
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let token_id = env::predecessor_account_id();
+        // 只接受白名單中的代幣
+        if !self.ft_whitelist.contains(&token_id) {
+            return PromiseOrValue::Value(amount);
+        }
+        let target = match near_sdk::serde_json::from_str::<ContentTarget>(&msg) {
+            Ok(target) => target,
+            Err(_) => return PromiseOrValue::Value(amount),
+        };
+        // 被移除的文章或留言不能打賞，被封鎖的帳號也不能打賞
+        let author = match self.target_author(&target) {
+            Open(author) | Locked(author) => author,
+            _ => return PromiseOrValue::Value(amount),
+        };
+        let board = self
+            .posts
+            .get(&target.post_id())
+            .and_then(|post_with_status| post_with_status.into_inner())
+            .and_then(|post| post.board);
+        if author == sender_id || self.active_ban(&sender_id, board.as_ref()).is_some() {
+            return PromiseOrValue::Value(amount);
+        }
+        let key = (author, token_id);
+        let balance = self.ft_balances.get(&key).unwrap_or(0);
+        self.ft_balances.insert(&key, &(balance + amount.0));
+        PromiseOrValue::Value(U128(0))
+    }
+
+    // 提領收到的代幣打賞（amount是null時全部提領，需要附加1 yoctoNEAR）
+    #[payable]
+    pub fn withdraw_ft(&mut self, token_id: AccountId, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        let account_id = env::signer_account_id();
+        let key = (account_id.clone(), token_id.clone());
+        let balance = self.ft_balances.get(&key).unwrap_or(0);
+        let amount = amount.map_or(balance, |amount| amount.0);
+        if amount == 0 || amount > balance {
+            BoardError::InvalidWithdrawal {
+                amount,
+                available: balance,
+            }
+            .panic();
+        }
+        // 先扣除餘額，轉帳失敗時在callback加回來
+        self.ft_balances.insert(&key, &(balance - amount));
+        ext_ft::ext(token_id.clone())
+            .with_attached_deposit(1)
+            .with_static_gas(FT_TRANSFER_GAS)
+            .ft_transfer(account_id.clone(), U128(amount), None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(TIP_CALLBACK_GAS)
+                    .on_ft_withdrawn(account_id, token_id, U128(amount)),
+            )
+    }
+
+    // 代幣轉帳失敗的話把餘額加回來
+    #[private]
+    pub fn on_ft_withdrawn(
+        &mut self,
+        account_id: AccountId,
+        token_id: AccountId,
+        amount: U128,
+    ) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                let key = (account_id, token_id);
+                let balance = self.ft_balances.get(&key).unwrap_or(0);
+                self.ft_balances.insert(&key, &(balance + amount.0));
+                false
+            }
+        }
+    }
+
+    // 查詢帳號收到的代幣打賞餘額
+    pub fn get_ft_balance(&self, account_id: AccountId, token_id: AccountId) -> U128 {
+        U128(self.ft_balances.get(&(account_id, token_id)).unwrap_or(0))
+    }
+
+    // 將代幣合約加入打賞白名單（僅限合約擁有者）
+    pub fn add_ft_token(&mut self, token_id: AccountId) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.ft_whitelist.insert(&token_id)
+    }
+
+    // 將代幣合約移出打賞白名單（僅限合約擁有者，已經收到的餘額仍然可以提領）
+    pub fn remove_ft_token(&mut self, token_id: AccountId) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.ft_whitelist.remove(&token_id)
+    }
+
+    // 查詢可以用來打賞的代幣合約
+    pub fn get_ft_tokens(&self) -> Vec<AccountId> {
+        self.ft_whitelist.to_vec()
+    }
+
+    // 選出懸賞文章的最佳解答並發放懸賞（作者隨時可以選，管理員要在截止時間之後）
+    pub fn accept_answer(&mut self, post_id: u128, comment_id: u128) -> WithStatus<Post> {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        let post = match self.load_post(post_id) {
+            Some(Open(post) | Locked(post)) => post,
+            _ => return Empty,
+        };
+        let deadline = match &post.bounty {
+            Some(bounty) => bounty.deadline,
+            None => return Empty,
+        };
+        let signer = env::signer_account_id();
+        if signer != post.creator_user_id
+            && !(self.is_moderator(&signer) && env::block_timestamp() >= deadline)
+        {
+            return Empty;
+        }
+        // 不能選自己的留言
+        match post.comment(comment_id) {
+            Some(Open(comment) | Locked(comment))
+                if comment.comment_creator_user_id != post.creator_user_id => {}
+            _ => return Empty,
+        }
+        self.settle_bounty(post_id, Some(comment_id))
+    }
+
+    // 截止時間加上寬限期過後還沒選出最佳解答的話，把懸賞退還給作者（任何人都可以呼叫）
+    pub fn refund_bounty(&mut self, post_id: u128) -> WithStatus<Post> {
+        let deadline = match self
+            .posts
+            .get(&post_id)
+            .and_then(|post_with_status| post_with_status.into_inner())
+            .and_then(|post| post.bounty)
+        {
+            Some(bounty) => bounty.deadline,
+            None => return Empty,
+        };
+        // 寬限期內管理員還可以選出最佳解答，不能搶先退還
+        if env::block_timestamp() < deadline.saturating_add(self.config.bounty_grace_period) {
+            BoardError::InvalidBounty {
+                reason: "the deadline and grace period have not passed yet",
+            }
+            .panic();
+        }
+        self.settle_bounty(post_id, None)
+    }
+
+    // 設定懸賞截止後的寬限期（僅限合約擁有者）
+    pub fn set_bounty_grace_period(&mut self, bounty_grace_period: u64) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.bounty_grace_period = bounty_grace_period;
+        true
+    }
+
+    // 投票（每個帳號只能投一次，投票結束或文章不是開放狀態時不能投票）
+    pub fn vote_poll(&mut self, post_id: u128, choices: Vec<u32>) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        let signer = env::signer_account_id();
+        let mut post = match self.load_post(post_id) {
+            Some(Open(post)) => post,
+            _ => return false,
+        };
+        let poll = match post.poll.as_mut() {
+            Some(poll) if env::block_timestamp() < poll.ends_at => poll,
+            _ => return false,
+        };
+        if poll.votes.iter().any(|(voter, _)| *voter == signer) {
+            return false;
+        }
+        let mut choices = choices;
+        choices.sort_unstable();
+        choices.dedup();
+        // 至少要選一個選項，單選的話只能選一個
+        if choices.is_empty()
+            || (!poll.multiple_choice && choices.len() > 1)
+            || choices
+                .iter()
+                .any(|choice| *choice as usize >= poll.options.len())
+        {
+            return false;
+        }
+        choices
+            .iter()
+            .for_each(|choice| poll.counts[*choice as usize] += 1);
+        poll.votes.push((signer, choices));
+        // 儲存文章
+        self.posts.insert(&post_id, &Open(post));
+        true
+    }
+
+    // 查詢投票結果（文章沒有投票或無法查看時回傳null）
+    pub fn get_poll_results(&self, post_id: u128) -> Option<PollResults> {
+        let (poll, open) = match self.load_post(post_id) {
+            Some(Open(post)) => (post.poll?, true),
+            Some(Locked(post)) => (post.poll?, false),
+            _ => return None,
+        };
+        Some(PollResults {
+            closed: !open || env::block_timestamp() >= poll.ends_at,
+            number_of_voters: poll.votes.len() as u64,
+            voters: if poll.voters_visible {
+                Some(poll.votes)
+            } else {
+                None
+            },
+            options: poll.options,
+            counts: poll.counts,
+            multiple_choice: poll.multiple_choice,
+            ends_at: poll.ends_at,
+        })
+    }
+
+    // 設定看板的持有資格（僅限管理員，null代表不限制）
+    pub fn set_board_gate(&mut self, name: String, gate: Option<BoardGate>) -> bool {
+        if !self.is_moderator(&env::signer_account_id()) {
+            return false;
+        }
+        match self.boards.get(&name) {
+            Some(mut board) => {
+                board.gate = gate;
+                self.boards.insert(&name, &board);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 向NFT或代幣合約查詢呼叫者是否有看板的持有資格，確認後在有效期間內發文與留言不需要再查詢
+    pub fn verify_membership(&mut self, board: String) -> Promise {
+        let gate = match self.boards.get(&board).and_then(|board| board.gate) {
+            Some(gate) => gate,
+            None => BoardError::NotGated { board }.panic(),
+        };
+        let account_id = env::signer_account_id();
+        let query = match gate {
+            BoardGate::Nft { contract_id } => {
+                ext_nft::ext(contract_id).nft_supply_for_owner(account_id.clone())
+            }
+            BoardGate::Ft { contract_id, .. } => {
+                ext_ft::ext(contract_id).ft_balance_of(account_id.clone())
+            }
+        };
+        query.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(TIP_CALLBACK_GAS)
+                .on_membership_checked(account_id, board),
+        )
+    }
+
+    // 持有數量足夠的話記錄持有資格的有效期限
+    #[private]
+    pub fn on_membership_checked(&mut self, account_id: AccountId, board: String) -> bool {
+        let amount = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                match near_sdk::serde_json::from_slice::<U128>(&value) {
+                    Ok(amount) => amount.0,
+                    Err(_) => return false,
+                }
+            }
+            _ => return false,
+        };
+        let is_member = match self.boards.get(&board).and_then(|board| board.gate) {
+            Some(BoardGate::Nft { .. }) => amount > 0,
+            Some(BoardGate::Ft { min_balance, .. }) => amount >= min_balance.0,
+            None => false,
+        };
+        let key = (account_id, board);
+        if is_member {
+            self.memberships
+                .insert(&key, &(env::block_timestamp() + self.config.membership_ttl));
+        } else {
+            self.memberships.remove(&key);
+        }
+        is_member
+    }
+
+    // 查詢帳號在看板的持有資格有效期限（沒有確認過或已經過期就是null）
+    pub fn get_membership(&self, account_id: AccountId, board: String) -> Option<u64> {
+        self.memberships
+            .get(&(account_id, board))
+            .filter(|expires_at| *expires_at > env::block_timestamp())
+    }
+
+    // 設定持有資格確認後的有效期間（僅限合約擁有者）
+    pub fn set_membership_ttl(&mut self, membership_ttl: u64) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.membership_ttl = membership_ttl;
+        true
+    }
+
+    // 把文章鑄造成NFT（僅限原作者，每篇文章只能鑄造一次，需要附加押金支付儲存費用）
+    // NFT記錄鑄造當下的標題、內文雜湊、作者與發文時間
+    #[payable]
+    pub fn mint_post(&mut self, post_id: u128) -> Token {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        let post = match self.load_post(post_id) {
+            Some(Open(post) | Locked(post)) => post,
+            _ => BoardError::CannotMint {
+                reason: "the post does not exist or has been removed",
+            }
+            .panic(),
+        };
+        let signer = env::signer_account_id();
+        if post.creator_user_id != signer {
+            BoardError::CannotMint {
+                reason: "only the author can mint the post",
+            }
+            .panic();
+        }
+        let token_id: TokenId = post_id.to_string();
+        if self.tokens.owner_by_id.get(&token_id).is_some() {
+            BoardError::CannotMint {
+                reason: "the post has already been minted",
+            }
+            .panic();
+        }
+        let content_hash = Base64VecU8(env::sha256(post.content.as_bytes()));
+        let metadata = TokenMetadata {
+            title: Some(post.title),
+            description: None,
+            media: None,
+            media_hash: None,
+            copies: Some(1),
+            issued_at: Some((env::block_timestamp() / 1_000_000).to_string()),
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            extra: Some(
+                near_sdk::serde_json::json!({
+                    "post_id": post_id.to_string(),
+                    "author": post.creator_user_id,
+                    "created_at": post.created_at.to_string(),
+                    "content_hash": content_hash,
+                })
+                .to_string(),
+            ),
+            reference: None,
+            reference_hash: None,
+        };
+        self.tokens.internal_mint(token_id, signer, Some(metadata))
+    }
+
+    // 查詢NFT售出時各帳號應得的金額，原作者可以得到版稅（NEP-199）
+    pub fn nft_payout(
+        &self,
+        token_id: TokenId,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        let owner_id = match self.tokens.owner_by_id.get(&token_id) {
+            Some(owner_id) => owner_id,
+            None => BoardError::CannotPayout {
+                reason: "token not found",
+            }
+            .panic(),
+        };
+        let author = token_id
+            .parse::<u128>()
+            .ok()
+            .and_then(|post_id| self.posts.get(&post_id))
+            .and_then(|post_with_status| post_with_status.into_inner())
+            .map(|post| post.creator_user_id);
+        let mut payout = HashMap::new();
+        match author {
+            Some(author) if author != owner_id => {
+                if max_len_payout.is_some_and(|max_len_payout| max_len_payout < 2) {
+                    BoardError::CannotPayout {
+                        reason: "too many receivers",
+                    }
+                    .panic();
+                }
+                let royalty =
+                    balance.0 * Balance::from(self.config.nft_royalty_basis_points) / 10_000;
+                payout.insert(author, U128(royalty));
+                payout.insert(owner_id, U128(balance.0 - royalty));
+            }
+            _ => {
+                payout.insert(owner_id, balance);
+            }
+        }
+        Payout { payout }
+    }
+
+    // 轉移NFT並回傳各帳號應得的金額（NEP-199，需要附加1 yoctoNEAR）
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        assert_one_yocto();
+        let payout = self.nft_payout(token_id.clone(), balance, max_len_payout);
+        self.tokens.internal_transfer(
+            &env::predecessor_account_id(),
+            &receiver_id,
+            &token_id,
+            approval_id,
+            memo,
+        );
+        payout
+    }
+
+    // 設定文章NFT售出時給原作者的版稅（萬分比，僅限合約擁有者）
+    pub fn set_nft_royalty(&mut self, nft_royalty_basis_points: u16) -> bool {
+        if env::signer_account_id() != self.owner_id || nft_royalty_basis_points > 10_000 {
+            return false;
+        }
+        self.config.nft_royalty_basis_points = nft_royalty_basis_points;
+        true
+    }
+
+    // 購買付費文章（附加的押金至少要等於價格，多付的會退回），購買紀錄會讓作者的金鑰伺服器發放解密金鑰
+    #[payable]
+    pub fn purchase_post(&mut self, post_id: u128) -> bool {
+        // 確認沒有被封鎖
+        self.assert_not_banned_in_post(post_id);
+        let post = match self.load_post(post_id) {
+            Some(Open(post) | Locked(post)) => post,
+            _ => BoardError::CannotPurchase {
+                reason: "the post does not exist or has been removed",
+            }
+            .panic(),
+        };
+        let price = match post.premium_price {
+            Some(price) => price.0,
+            None => BoardError::CannotPurchase {
+                reason: "the post is not a premium post",
+            }
+            .panic(),
+        };
+        let buyer = env::signer_account_id();
+        let key = (post_id, buyer.clone());
+        if buyer == post.creator_user_id || self.purchases.get(&key).is_some() {
+            BoardError::CannotPurchase {
+                reason: "the post has already been purchased",
+            }
+            .panic();
+        }
+        let attached_deposit = env::attached_deposit();
+        if attached_deposit < price {
+            BoardError::InsufficientDeposit { required: price }.panic();
+        }
+        self.purchases.insert(&key, &env::block_timestamp());
+        let revenue = self.premium_revenue.get(&post.creator_user_id).unwrap_or(0);
+        self.premium_revenue
+            .insert(&post.creator_user_id, &(revenue + price));
+        // 退回多付的押金（與charge_storage一樣退給predecessor）
+        if attached_deposit > price {
+            Promise::new(env::predecessor_account_id()).transfer(attached_deposit - price);
+        }
+        true
+    }
+
+    // 查詢帳號是否可以閱讀付費文章（作者本人或已購買），給金鑰伺服器確認用
+    pub fn has_purchased(&self, post_id: u128, account_id: AccountId) -> bool {
+        let author = self
+            .posts
+            .get(&post_id)
+            .and_then(|post_with_status| post_with_status.into_inner())
+            .map(|post| post.creator_user_id);
+        author == Some(account_id.clone()) || self.purchases.get(&(post_id, account_id)).is_some()
+    }
+
+    // 查詢作者可以提領的付費文章收入
+    pub fn get_premium_revenue(&self, account_id: AccountId) -> U128 {
+        U128(self.premium_revenue.get(&account_id).unwrap_or(0))
+    }
+
+    // 提領付費文章收入（需要附加1 yoctoNEAR）
+    #[payable]
+    pub fn withdraw_revenue(&mut self) -> Promise {
+        assert_one_yocto();
+        let account_id = env::signer_account_id();
+        let revenue = self.premium_revenue.get(&account_id).unwrap_or(0);
+        if revenue == 0 {
+            BoardError::InvalidWithdrawal {
+                amount: revenue,
+                available: revenue,
+            }
+            .panic();
+        }
+        // 先扣除收入，轉帳失敗時在callback加回來
+        self.premium_revenue.remove(&account_id);
+        Promise::new(account_id.clone()).transfer(revenue).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(TIP_CALLBACK_GAS)
+                .on_revenue_withdrawn(account_id, U128(revenue)),
+        )
+    }
+
+    // 轉帳失敗的話把收入加回來
+    #[private]
+    pub fn on_revenue_withdrawn(&mut self, account_id: AccountId, amount: U128) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                let revenue = self.premium_revenue.get(&account_id).unwrap_or(0);
+                self.premium_revenue
+                    .insert(&account_id, &(revenue + amount.0));
+                false
+            }
+        }
+    }
+
+    // 提出提案（需要足夠的聲望），回傳提案流水號
+    pub fn create_proposal(&mut self, action: ProposalAction, description: String) -> Option<u64> {
+        // 確認沒有被全站封鎖
+        self.assert_not_banned(None);
+        let signer = env::signer_account_id();
+        if self.karma_of(&signer) < self.config.governance.min_karma_to_propose {
+            return None;
+        }
+        let id = self.number_of_proposals;
+        let created_at = env::block_timestamp();
+        let governance = &self.config.governance;
+        let voting_mode = if action.is_privileged() {
+            VotingMode::ByKarma
+        } else {
+            governance.voting_mode
+        };
+        let quorum = match voting_mode {
+            VotingMode::ByAccount => governance.account_quorum,
+            VotingMode::ByKarma => governance.karma_quorum,
+        };
+        self.proposals.insert(
+            &id,
+            &Proposal {
+                id,
+                proposer: signer,
+                description,
+                action,
+                voting_mode,
+                created_at,
+                karma_cutoff_at: created_at.saturating_sub(governance.karma_cutoff),
+                quorum,
+                ends_at: created_at + governance.voting_period,
+                yes: 0,
+                no: 0,
+                voters: Vec::default(),
+                status: ProposalStatus::InProgress,
+            },
+        );
+        self.number_of_proposals += 1;
+        Some(id)
+    }
+
+    // 對提案投票（每個帳號只能投一次，提案前就有投票資格的帳號才能投票），投票截止後會自動結算提案
+    pub fn vote_proposal(&mut self, proposal_id: u64, approve: bool) -> bool {
+        // 確認沒有被全站封鎖
+        self.assert_not_banned(None);
+        let mut proposal = match self.proposals.get(&proposal_id) {
+            Some(proposal) => proposal,
+            None => return false,
+        };
+        if env::block_timestamp() >= proposal.ends_at {
+            self.finalize(proposal);
+            return false;
+        }
+        let signer = env::signer_account_id();
+        let weight = if !self.can_vote_on(&signer, &proposal) {
+            0
+        } else {
+            match proposal.voting_mode {
+                VotingMode::ByAccount => 1,
+                VotingMode::ByKarma => self.karma_at(&signer, proposal.karma_cutoff_at),
+            }
+        };
+        if proposal.status != ProposalStatus::InProgress
+            || weight == 0
+            || proposal.voters.contains(&signer)
+        {
+            return false;
+        }
+        if approve {
+            proposal.yes += weight;
+        } else {
+            proposal.no += weight;
+        }
+        proposal.voters.push(signer);
+        self.proposals.insert(&proposal_id, &proposal);
+        true
+    }
+
+    // 結算投票已經截止的提案（任何人都可以呼叫），通過的提案會自動執行
+    pub fn finalize_proposal(&mut self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals
+            .get(&proposal_id)
+            .map(|proposal| self.finalize(proposal))
+    }
+
+    // 查詢提案（新的在前，分頁）
+    pub fn get_proposals(&self, from: u64, limit: u64) -> Vec<Proposal> {
+        (0..self.number_of_proposals)
+            .rev()
+            .skip(from as usize)
+            .take(limit as usize)
+            .filter_map(|id| self.proposals.get(&id))
+            .collect()
+    }
+
+    // 設定提案的計票方式、門檻與投票期間（僅限合約擁有者）
+    pub fn set_governance_config(&mut self, governance: GovernanceConfig) -> bool {
+        if env::signer_account_id() != self.owner_id {
+            return false;
+        }
+        self.config.governance = governance;
+        true
+    }
+
+    // 寄站內信給已經公開公鑰的帳號（儲存費用由寄件者支付），回傳站內信流水號
+    #[payable]
+    pub fn send_message(&mut self, to: AccountId, ciphertext: String, nonce: String) -> u64 {
+        // 確認沒有被全站封鎖
+        self.assert_not_banned(None);
+        let initial_storage_usage = env::storage_usage();
+        let from = env::signer_account_id();
+        if ciphertext.is_empty() {
+            BoardError::EmptyField { field: "message" }.panic();
+        }
+        if ciphertext.len() + nonce.len() > self.config.limits.max_message_bytes as usize {
+            BoardError::TooLong {
+                field: "message",
+                max: self.config.limits.max_message_bytes,
+                unit: "bytes",
+            }
+            .panic();
+        }
+        if to == from {
+            BoardError::CannotMessage {
+                reason: "cannot send a message to yourself",
+            }
+            .panic();
+        }
+        // 收件者必須公開公鑰，而且沒有拒收寄件者的站內信
+        if self.public_keys.get(&to).is_none() {
+            BoardError::CannotMessage {
+                reason: "the recipient has not published a public key",
+            }
+            .panic();
+        }
+        if self
+            .blocked_accounts
+            .get(&to)
+            .unwrap_or_default()
+            .contains(&from)
+        {
+            BoardError::CannotMessage {
+                reason: "the recipient does not accept messages from this account",
+            }
+            .panic();
+        }
+        let id = self.number_of_messages;
+        self.messages.insert(
+            &id,
+            &Message {
+                id,
+                from: from.clone(),
+                to: to.clone(),
+                ciphertext,
+                nonce,
+                sent_at: env::block_timestamp(),
+            },
+        );
+        self.number_of_messages += 1;
+        let mut inbox = self.inbox_messages.get(&to).unwrap_or_default();
+        inbox.push(id);
+        self.inbox_messages.insert(&to, &inbox);
+        let mut outbox = self.outbox_messages.get(&from).unwrap_or_default();
+        outbox.push(id);
+        self.outbox_messages.insert(&from, &outbox);
+        // 收取儲存費用
+        self.charge_storage(initial_storage_usage);
+        id
+    }
+
+    // 查詢帳號的收件匣（新的在前，分頁）
+    pub fn get_inbox(&self, account_id: AccountId, from: u64, limit: u64) -> Vec<Message> {
+        self.inbox_messages
+            .get(&account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .skip(from as usize)
+            .take(limit as usize)
+            .filter_map(|id| self.messages.get(&id))
+            .collect()
+    }
+
+    // 查詢帳號的寄件備份（新的在前，分頁）
+    pub fn get_outbox(&self, account_id: AccountId, from: u64, limit: u64) -> Vec<Message> {
+        self.outbox_messages
+            .get(&account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .skip(from as usize)
+            .take(limit as usize)
+            .filter_map(|id| self.messages.get(&id))
+            .collect()
+    }
+
+    // 從自己的收件匣與寄件備份刪除站內信，雙方都刪除後站內信才會從鏈上移除
+    // 釋放的儲存費用退給當初支付的寄件者
+    pub fn delete_message(&mut self, message_id: u64) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let message = match self.messages.get(&message_id) {
+            Some(message) => message,
+            None => return false,
+        };
+        let mut deleted = false;
+        for (mailbox, owner) in [
+            (&mut self.inbox_messages, &message.to),
+            (&mut self.outbox_messages, &message.from),
+        ] {
+            if *owner != signer {
+                continue;
+            }
+            let mut ids = mailbox.get(owner).unwrap_or_default();
+            let number_of_ids = ids.len();
+            ids.retain(|id| *id != message_id);
+            if ids.len() != number_of_ids {
+                mailbox.insert(owner, &ids);
+                deleted = true;
+            }
+        }
+        // 雙方都刪除了就移除站內信
+        let still_kept = |mailbox: &LookupMap<AccountId, Vec<u64>>, owner: &AccountId| {
+            mailbox.get(owner).unwrap_or_default().contains(&message_id)
+        };
+        if !still_kept(&self.inbox_messages, &message.to)
+            && !still_kept(&self.outbox_messages, &message.from)
+        {
+            self.messages.remove(&message_id);
+        }
+        self.refund_storage(initial_storage_usage, &message.from);
+        deleted
+    }
+
+    // 拒收帳號的站內信（由呼叫者支付儲存空間費用）
+    #[payable]
+    pub fn block_account(&mut self, account_id: AccountId) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let mut blocked_accounts = self.blocked_accounts.get(&signer).unwrap_or_default();
+        if account_id == signer || blocked_accounts.contains(&account_id) {
+            self.refund_deposit();
+            return false;
+        }
+        if blocked_accounts.len() >= self.config.limits.max_blocked_accounts as usize {
+            BoardError::LimitReached {
+                what: "blocked accounts",
+                max: self.config.limits.max_blocked_accounts,
+            }
+            .panic();
+        }
+        blocked_accounts.push(account_id);
+        self.blocked_accounts.insert(&signer, &blocked_accounts);
+        self.charge_storage(initial_storage_usage);
+        true
+    }
+
+    // 取消拒收帳號的站內信（退還釋放的儲存空間費用）
+    pub fn unblock_account(&mut self, account_id: AccountId) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let signer = env::signer_account_id();
+        let mut blocked_accounts = self.blocked_accounts.get(&signer).unwrap_or_default();
+        if !blocked_accounts.contains(&account_id) {
+            return false;
+        }
+        blocked_accounts.retain(|blocked| *blocked != account_id);
+        if blocked_accounts.is_empty() {
+            self.blocked_accounts.remove(&signer);
+        } else {
+            self.blocked_accounts.insert(&signer, &blocked_accounts);
+        }
+        self.refund_storage(initial_storage_usage, &signer);
+        true
+    }
+
+    // 查詢帳號拒收站內信的帳號
+    pub fn get_blocked_accounts(&self, account_id: AccountId) -> Vec<AccountId> {
+        self.blocked_accounts.get(&account_id).unwrap_or_default()
+    }
+}
+
+// 文章NFT（NEP-171/177/178/181）
+near_contract_standards::impl_non_fungible_token_core!(BulletinBoard, tokens);
+near_contract_standards::impl_non_fungible_token_approval!(BulletinBoard, tokens);
+near_contract_standards::impl_non_fungible_token_enumeration!(BulletinBoard, tokens);
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for BulletinBoard {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Blockchain Bulletin Board Posts".to_string(),
+            symbol: "POST".to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    // 合約帳號（同時也是合約擁有者）
+    const CONTRACT: &str = "board.near";
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    // 以指定帳號呼叫合約的環境
+    fn context(account_id: &str) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(account(CONTRACT))
+            .signer_account_id(account(account_id))
+            .predecessor_account_id(account(account_id));
+        builder
+    }
+
+    // 跨合約呼叫的callback（由合約自己呼叫，帶有前一個呼叫的結果）
+    fn callback_context(result: PromiseResult) {
+        testing_env!(
+            context(CONTRACT).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
     }
 
-    // 透過使用者ID查詢文章
-    pub fn search_post_by_user_id(
-        &self,
-        creator_user_id: AccountId,
-    ) -> Vec<(u128, WithStatus<Post>)> {
-        self.posts
-            .to_vec()
+    // 這次呼叫轉給帳號的金額
+    fn transferred_to(account_id: &str) -> Balance {
+        get_created_receipts()
             .into_iter()
-            // 僅有狀態為開放與鎖定的文章可以被查詢到
-            .filter(|(_, post_with_status)| match post_with_status {
-                Open(post) | Locked(post) => creator_user_id == post.creator_user_id,
-                _ => false,
+            .filter(|receipt| receipt.receiver_id == account(account_id))
+            .flat_map(|receipt| receipt.actions)
+            .map(|action| match action {
+                VmAction::Transfer { deposit } => deposit,
+                _ => 0,
             })
-            .collect::<Vec<(u128, WithStatus<Post>)>>()
+            .sum()
     }
 
-    // 點讚
-    pub fn like_post(&mut self, post_id: u128) -> WithStatus<Post> {
-        self.posts
-            .to_vec()
-            .into_iter()
-            .map(
-                |(id, post_with_status)| match (id == post_id, post_with_status) {
-                    // 僅有狀態為開放的文章可以被點讚
-                    (true, Open(mut post)) => {
-                        // 將自己的使用者ID存入
-                        post.users_who_liked.push(env::signer_account_id());
-                        // 儲存改好的文章
-                        self.posts.insert(&id, &Open(post.clone()));
-                        // 回傳文章
-                        Open(post)
-                    }
-                    // 不符合條件，回傳找不到
-                    _ => Empty,
+    // 以合約帳號部署的合約（合約帳號就是擁有者）
+    fn new_contract() -> BulletinBoard {
+        testing_env!(context(CONTRACT).build());
+        BulletinBoard::default()
+    }
+
+    // 以指定帳號發文，回傳文章流水號
+    fn add_post(contract: &mut BulletinBoard, account_id: &str) -> u128 {
+        testing_env!(context(account_id).build());
+        match contract.add_post("title".to_string(), "content".to_string(), Vec::new(), None) {
+            Open(post) => post.id,
+            _ => panic!("add_post failed"),
+        }
+    }
+
+    // 以指定帳號留言，回傳留言流水號
+    fn add_comment(
+        contract: &mut BulletinBoard,
+        account_id: &str,
+        post_id: u128,
+        parent_id: Option<u128>,
+    ) -> u128 {
+        testing_env!(context(account_id).build());
+        match contract.add_comment(post_id, parent_id, "comment".to_string(), None) {
+            Open(post) => post.comments.last().unwrap().inner().unwrap().id,
+            _ => panic!("add_comment failed"),
+        }
+    }
+
+    #[test]
+    fn comment_targets_use_stable_ids() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        add_comment(&mut contract, "bob.near", post_id, None);
+        let comment_id = add_comment(&mut contract, "carol.near", post_id, None);
+        let target = ContentTarget::Comment {
+            post_id,
+            comment_id,
+        };
+        testing_env!(context("dave.near").build());
+        assert!(contract.add_emoji_reaction(target.clone(), "👍".to_string()));
+        // 修改其他留言不會影響表情符號的對象
+        testing_env!(context("bob.near").build());
+        assert!(matches!(
+            contract.edit_comment(post_id, 0, "edited".to_string(), None),
+            Open(_)
+        ));
+        assert_eq!(
+            contract.get_reactions(target.clone()),
+            vec![("👍".to_string(), 1)]
+        );
+        // 留言在陣列中的位置改變，仍然用流水號找到同一則留言
+        let mut post = contract.posts.get(&post_id).unwrap().into_inner().unwrap();
+        post.comments.reverse();
+        contract.posts.insert(&post_id, &Open(post));
+        assert!(matches!(
+            contract.target_author(&target),
+            Open(author) if author == account("carol.near")
+        ));
+    }
+
+    #[test]
+    fn dismiss_restores_status_before_auto_hide() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        let comment_id = add_comment(&mut contract, "bob.near", post_id, None);
+        let target = ContentTarget::Comment {
+            post_id,
+            comment_id,
+        };
+        testing_env!(context(CONTRACT).build());
+        assert!(contract.set_auto_hide_report_threshold(Some(1)));
+        contract.set_target_status(&target, "Locked");
+        testing_env!(context("carol.near").build());
+        assert!(contract.report(target.clone(), "spam".to_string()));
+        assert_eq!(contract.target_status(&target).as_deref(), Some("Removed"));
+        // 自動隱藏不會把檢舉者記錄成移除者
+        let removed = contract.get_my_removed(account("bob.near"));
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].removed_by.is_none());
+        // 駁回後恢復成隱藏前的鎖定狀態，而不是開放
+        testing_env!(context(CONTRACT).build());
+        assert!(contract.resolve_report(target.clone(), ModerationAction::Dismiss));
+        assert_eq!(contract.target_status(&target).as_deref(), Some("Locked"));
+    }
+
+    #[test]
+    fn rejected_writes_do_not_use_rate_limit() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        // 在不存在的文章留言會被拒絕，不會用掉留言額度
+        testing_env!(context("bob.near").build());
+        (0..20).for_each(|_| {
+            assert!(matches!(
+                contract.add_comment(post_id + 1, None, "comment".to_string(), None),
+                Empty
+            ));
+        });
+        assert!(contract.recent_writes.get(&account("bob.near")).is_none());
+        add_comment(&mut contract, "bob.near", post_id, None);
+        assert_eq!(
+            contract
+                .recent_writes
+                .get(&account("bob.near"))
+                .unwrap()
+                .comments
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn only_moderators_restore_moderator_removals() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        let own_comment = add_comment(&mut contract, "bob.near", post_id, None);
+        let removed_comment = add_comment(&mut contract, "bob.near", post_id, None);
+        // 自己移除的留言可以自己復原
+        assert!(matches!(
+            contract.edit_comment(
+                post_id,
+                own_comment,
+                String::new(),
+                Some("Removed".to_string())
+            ),
+            Open(_)
+        ));
+        assert!(matches!(
+            contract.restore_comment(post_id, own_comment),
+            Open(_)
+        ));
+        // 管理員處理檢舉移除的留言，原作者不能復原
+        let target = ContentTarget::Comment {
+            post_id,
+            comment_id: removed_comment,
+        };
+        testing_env!(context("carol.near").build());
+        assert!(contract.report(target.clone(), "spam".to_string()));
+        testing_env!(context(CONTRACT).build());
+        assert!(contract.resolve_report(target, ModerationAction::Remove));
+        testing_env!(context("bob.near").build());
+        assert!(matches!(
+            contract.restore_comment(post_id, removed_comment),
+            Empty
+        ));
+        // 垃圾桶列出移除者，內容只有本人透過交易查詢時才會列出
+        let removed = contract.get_my_removed(account("bob.near"));
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].removed_by == Some(account(CONTRACT)));
+        assert!(removed[0].content.is_none());
+        let removed = contract.get_my_removed_with_content();
+        assert_eq!(removed[0].content.as_deref(), Some("comment"));
+        testing_env!(context(CONTRACT).build());
+        assert!(matches!(
+            contract.restore_comment(post_id, removed_comment),
+            Open(_)
+        ));
+    }
+
+    // 儲存空間押金（測試中多付的部分會退回）
+    const STORAGE_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
+
+    #[test]
+    fn storage_deposits_are_refunded_to_the_caller() {
+        let mut contract = new_contract();
+        // 透過其他合約呼叫時，多付的押金退給附加押金的合約
+        testing_env!(context("alice.near")
+            .predecessor_account_id(account("relay.near"))
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        let storage_usage = env::storage_usage();
+        contract.set_profile(Some("Alice".to_string()), None, None, None, None);
+        let storage_cost =
+            Balance::from(env::storage_usage() - storage_usage) * env::storage_byte_cost();
+        assert_eq!(transferred_to("relay.near"), STORAGE_DEPOSIT - storage_cost);
+        assert_eq!(transferred_to("alice.near"), 0);
+        // 個人資料還是存在signer底下
+        assert!(contract
+            .get_profile(account("alice.near"))
+            .profile
+            .is_some());
+    }
+
+    #[test]
+    fn follows_are_paid_by_the_follower() {
+        let mut contract = new_contract();
+        testing_env!(context("bob.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        assert!(contract.follow_account(account("alice.near")));
+        assert!(contract.follow_tag("Rust".to_string()));
+        assert_eq!(
+            contract.get_followers(account("alice.near"), 0, 10),
+            vec![account("bob.near")]
+        );
+        assert_eq!(
+            contract.get_following(account("bob.near")).tags,
+            vec!["rust"]
+        );
+    }
+
+    #[test]
+    fn author_index_keeps_latest_posts() {
+        let mut contract = new_contract();
+        contract.config.limits.max_feed_posts_per_author = 2;
+        (0..3).for_each(|_| {
+            add_post(&mut contract, "alice.near");
+        });
+        assert_eq!(
+            contract.posts_by_author.get(&account("alice.near")),
+            Some(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn bookmarks_are_paid_by_the_owner() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        testing_env!(context("bob.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        let storage_usage = env::storage_usage();
+        assert!(contract.bookmark_post(post_id, Some(" reading ".to_string())));
+        assert!(env::storage_usage() > storage_usage);
+        let bookmarks = contract.get_bookmarks(account("bob.near"), None, 0, 10);
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].folder.as_deref(), Some("reading"));
+        // 收藏不存在的文章會被拒絕
+        assert!(!contract.bookmark_post(post_id + 1, None));
+        assert!(contract.unbookmark_post(post_id));
+        assert!(contract
+            .get_bookmarks(account("bob.near"), None, 0, 10)
+            .is_empty());
+    }
+
+    // 設定站內信公鑰
+    fn publish_public_key(contract: &mut BulletinBoard, account_id: &str) {
+        testing_env!(context(account_id)
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        contract.set_profile(None, None, None, None, Some("ed25519:key".to_string()));
+    }
+
+    #[test]
+    fn messages_are_removed_after_both_sides_delete_them() {
+        let mut contract = new_contract();
+        publish_public_key(&mut contract, "alice.near");
+        assert_eq!(
+            contract
+                .get_profile(account("alice.near"))
+                .public_key
+                .as_deref(),
+            Some("ed25519:key")
+        );
+        testing_env!(context("bob.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        let message_id = contract.send_message(
+            account("alice.near"),
+            "ciphertext".to_string(),
+            "nonce".to_string(),
+        );
+        let inbox = contract.get_inbox(account("alice.near"), 0, 10);
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].from, account("bob.near"));
+        assert_eq!(contract.get_outbox(account("bob.near"), 0, 10).len(), 1);
+        // 收件者刪除後，寄件備份還在
+        testing_env!(context("alice.near").build());
+        assert!(contract.delete_message(message_id));
+        assert!(!contract.delete_message(message_id));
+        assert!(contract.get_inbox(account("alice.near"), 0, 10).is_empty());
+        assert_eq!(contract.get_outbox(account("bob.near"), 0, 10).len(), 1);
+        // 雙方都刪除後站內信從鏈上移除，儲存費用退給寄件者
+        testing_env!(context("bob.near").build());
+        let storage_usage = env::storage_usage();
+        assert!(contract.delete_message(message_id));
+        assert!(contract.get_outbox(account("bob.near"), 0, 10).is_empty());
+        assert!(contract.messages.get(&message_id).is_none());
+        assert_eq!(
+            transferred_to("bob.near"),
+            Balance::from(storage_usage - env::storage_usage()) * env::storage_byte_cost()
+        );
+    }
+
+    #[test]
+    fn blocked_accounts_can_be_unblocked() {
+        let mut contract = new_contract();
+        publish_public_key(&mut contract, "alice.near");
+        testing_env!(context("alice.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        // 不能拒收自己，押金全數退還
+        assert!(!contract.block_account(account("alice.near")));
+        assert_eq!(transferred_to("alice.near"), STORAGE_DEPOSIT);
+        testing_env!(context("alice.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        let storage_usage = env::storage_usage();
+        assert!(contract.block_account(account("bob.near")));
+        let storage_cost =
+            Balance::from(env::storage_usage() - storage_usage) * env::storage_byte_cost();
+        assert_eq!(transferred_to("alice.near"), STORAGE_DEPOSIT - storage_cost);
+        assert!(contract.get_blocked_accounts(account("alice.near")) == vec![account("bob.near")]);
+        // 取消拒收時退還儲存空間費用
+        testing_env!(context("alice.near").build());
+        assert!(contract.unblock_account(account("bob.near")));
+        assert_eq!(transferred_to("alice.near"), storage_cost);
+        assert!(!contract.unblock_account(account("bob.near")));
+        assert!(contract
+            .get_blocked_accounts(account("alice.near"))
+            .is_empty());
+        // 取消拒收後可以再收到站內信
+        testing_env!(context("bob.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        let message_id = contract.send_message(
+            account("alice.near"),
+            "ciphertext".to_string(),
+            "nonce".to_string(),
+        );
+        assert_eq!(
+            contract.get_inbox(account("alice.near"), 0, 10)[0].id,
+            message_id
+        );
+    }
+
+    // 1 NEAR
+    const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+    #[test]
+    fn tips_collect_fees_for_the_owner() {
+        let mut contract = new_contract();
+        assert!(contract.set_tip_fee(500));
+        let post_id = add_post(&mut contract, "alice.near");
+        let target = ContentTarget::Post { post_id };
+        // 打賞時扣除5%的平台抽成，其餘轉給作者
+        testing_env!(context("bob.near").attached_deposit(ONE_NEAR).build());
+        contract.tip_post(post_id);
+        assert_eq!(transferred_to("alice.near"), ONE_NEAR / 100 * 95);
+        callback_context(PromiseResult::Successful(Vec::new()));
+        assert!(contract.on_tip_transferred(
+            target.clone(),
+            account("bob.near"),
+            account("alice.near"),
+            U128(ONE_NEAR / 100 * 95),
+            U128(ONE_NEAR / 100 * 5),
+        ));
+        assert_eq!(contract.get_tips(target).0, ONE_NEAR / 100 * 95);
+        assert_eq!(
+            contract.get_author_tips(account("alice.near")).0,
+            ONE_NEAR / 100 * 95
+        );
+        assert_eq!(contract.get_collected_fees().0, ONE_NEAR / 100 * 5);
+        // 合約擁有者提領平台抽成
+        testing_env!(context(CONTRACT).attached_deposit(1).build());
+        contract.withdraw_fees(None);
+        assert_eq!(transferred_to(CONTRACT), ONE_NEAR / 100 * 5);
+        assert_eq!(contract.get_collected_fees().0, 0);
+        // 轉帳失敗的話加回來
+        callback_context(PromiseResult::Failed);
+        assert!(!contract.on_fees_withdrawn(U128(ONE_NEAR / 100 * 5)));
+        assert_eq!(contract.get_collected_fees().0, ONE_NEAR / 100 * 5);
+    }
+
+    #[test]
+    fn failed_tips_are_refunded_with_the_fee() {
+        let mut contract = new_contract();
+        assert!(contract.set_tip_fee(500));
+        let post_id = add_post(&mut contract, "alice.near");
+        let comment_id = add_comment(&mut contract, "carol.near", post_id, None);
+        let target = ContentTarget::Comment {
+            post_id,
+            comment_id,
+        };
+        testing_env!(context("bob.near").attached_deposit(ONE_NEAR).build());
+        contract.tip_comment(post_id, comment_id);
+        assert_eq!(transferred_to("carol.near"), ONE_NEAR / 100 * 95);
+        // 轉帳失敗，打賞金額與平台抽成全數退回給打賞者
+        callback_context(PromiseResult::Failed);
+        assert!(!contract.on_tip_transferred(
+            target.clone(),
+            account("bob.near"),
+            account("carol.near"),
+            U128(ONE_NEAR / 100 * 95),
+            U128(ONE_NEAR / 100 * 5),
+        ));
+        assert_eq!(transferred_to("bob.near"), ONE_NEAR);
+        assert_eq!(contract.get_tips(target).0, 0);
+        assert_eq!(contract.get_collected_fees().0, 0);
+    }
+
+    // 以指定帳號發懸賞文章，回傳文章流水號
+    fn add_bounty_post(contract: &mut BulletinBoard, account_id: &str, deadline: u64) -> u128 {
+        testing_env!(context(account_id).attached_deposit(ONE_NEAR).build());
+        match contract.add_post(
+            "question".to_string(),
+            "content".to_string(),
+            Vec::new(),
+            Some(PostOptions {
+                bounty_deadline: Some(deadline),
+                ..PostOptions::default()
+            }),
+        ) {
+            Open(post) => post.id,
+            _ => panic!("add_post failed"),
+        }
+    }
+
+    #[test]
+    fn bounty_is_escrowed_and_awarded_to_the_accepted_answer() {
+        let mut contract = new_contract();
+        let post_id = add_bounty_post(&mut contract, "alice.near", 1_000);
+        let bounty = contract
+            .posts
+            .get(&post_id)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+            .bounty;
+        assert!(bounty.is_some_and(|bounty| bounty.amount.0 == ONE_NEAR && !bounty.settled));
+        let comment_id = add_comment(&mut contract, "bob.near", post_id, None);
+        testing_env!(context("alice.near").build());
+        let post = match contract.accept_answer(post_id, comment_id) {
+            Locked(post) => post,
+            _ => panic!("the post should be locked after the bounty is settled"),
+        };
+        assert!(
+            post.bounty.is_some_and(
+                |bounty| bounty.settled && bounty.accepted_comment_id == Some(comment_id)
+            )
+        );
+        assert_eq!(transferred_to("bob.near"), ONE_NEAR);
+        // 已經結算的懸賞不能再退還
+        testing_env!(context("carol.near").block_timestamp(u64::MAX).build());
+        assert!(matches!(contract.refund_bounty(post_id), Empty));
+        assert_eq!(transferred_to("alice.near"), 0);
+    }
+
+    #[test]
+    fn moderators_award_during_the_grace_period_before_refunds() {
+        let mut contract = new_contract();
+        let grace_period = contract.config.bounty_grace_period;
+        let awarded = add_bounty_post(&mut contract, "alice.near", 1_000);
+        let refunded = add_bounty_post(&mut contract, "alice.near", 1_000);
+        let comment_id = add_comment(&mut contract, "bob.near", awarded, None);
+        // 截止前管理員不能選出最佳解答
+        testing_env!(context(CONTRACT).block_timestamp(999).build());
+        assert!(matches!(contract.accept_answer(awarded, comment_id), Empty));
+        // 截止後的寬限期內，管理員可以選出最佳解答
+        testing_env!(context(CONTRACT).block_timestamp(1_000).build());
+        assert!(matches!(
+            contract.accept_answer(awarded, comment_id),
+            Locked(_)
+        ));
+        assert_eq!(transferred_to("bob.near"), ONE_NEAR);
+        // 寬限期過後，任何人都可以把懸賞退還給作者
+        testing_env!(context("carol.near")
+            .block_timestamp(1_000 + grace_period)
+            .build());
+        assert!(matches!(contract.refund_bounty(refunded), Locked(_)));
+        assert_eq!(transferred_to("alice.near"), ONE_NEAR);
+    }
+
+    #[test]
+    fn minted_posts_pay_royalties_to_the_author() {
+        use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
+
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        let token_id = post_id.to_string();
+        testing_env!(context("alice.near")
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        let token = contract.mint_post(post_id);
+        assert_eq!(token.owner_id, account("alice.near"));
+        // 作者持有時全部歸作者
+        let payout = contract.nft_payout(token_id.clone(), U128(1_000), None);
+        assert_eq!(payout.payout.len(), 1);
+        assert_eq!(payout.payout[&account("alice.near")].0, 1_000);
+        // 轉給bob
+        testing_env!(context("alice.near").attached_deposit(1).build());
+        contract.nft_transfer(account("bob.near"), token_id.clone(), None, None);
+        assert_eq!(
+            contract.nft_token(token_id.clone()).unwrap().owner_id,
+            account("bob.near")
+        );
+        // bob售出時，原作者得到10%的版稅
+        testing_env!(context("bob.near").attached_deposit(1).build());
+        let payout = contract.nft_transfer_payout(
+            account("carol.near"),
+            token_id.clone(),
+            None,
+            None,
+            U128(1_000),
+            Some(2),
+        );
+        assert_eq!(payout.payout[&account("alice.near")].0, 100);
+        assert_eq!(payout.payout[&account("bob.near")].0, 900);
+        assert_eq!(
+            contract.nft_token(token_id).unwrap().owner_id,
+            account("carol.near")
+        );
+    }
+
+    #[test]
+    fn premium_revenue_is_withdrawn_by_the_author() {
+        let mut contract = new_contract();
+        testing_env!(context("alice.near").build());
+        let post_id = match contract.add_post(
+            "premium".to_string(),
+            "ZW5jcnlwdGVk".to_string(),
+            Vec::new(),
+            Some(PostOptions {
+                premium_price: Some(U128(ONE_NEAR)),
+                ..PostOptions::default()
+            }),
+        ) {
+            Open(post) => post.id,
+            _ => panic!("add_post failed"),
+        };
+        // 多付的押金退回給購買者
+        testing_env!(context("bob.near").attached_deposit(2 * ONE_NEAR).build());
+        assert!(contract.purchase_post(post_id));
+        assert_eq!(transferred_to("bob.near"), ONE_NEAR);
+        assert!(contract.has_purchased(post_id, account("bob.near")));
+        assert_eq!(
+            contract.get_premium_revenue(account("alice.near")).0,
+            ONE_NEAR
+        );
+        testing_env!(context("alice.near").attached_deposit(1).build());
+        contract.withdraw_revenue();
+        assert_eq!(transferred_to("alice.near"), ONE_NEAR);
+        assert_eq!(contract.get_premium_revenue(account("alice.near")).0, 0);
+    }
+
+    // 發過文、聲望為karma的帳號（發文時間是0）
+    fn add_voter(contract: &mut BulletinBoard, account_id: &str, karma: u64) {
+        add_post(contract, account_id);
+        contract.set_karma(&account(account_id), karma);
+    }
+
+    #[test]
+    fn approved_proposals_are_executed_after_voting_ends() {
+        let mut contract = new_contract();
+        let governance = contract.config.governance.clone();
+        add_voter(&mut contract, "alice.near", 400);
+        add_voter(&mut contract, "bob.near", 400);
+        add_voter(&mut contract, "carol.near", 400);
+        let created_at = governance.min_account_age;
+        testing_env!(context("alice.near").block_timestamp(created_at).build());
+        let proposal_id = contract
+            .create_proposal(
+                ProposalAction::AddModerator {
+                    account_id: account("dave.near"),
                 },
+                "add dave".to_string(),
             )
-            .next()
-            .unwrap_or(Empty)
+            .unwrap();
+        assert!(contract.vote_proposal(proposal_id, true));
+        // 同一個帳號只能投一次
+        assert!(!contract.vote_proposal(proposal_id, true));
+        testing_env!(context("bob.near").block_timestamp(created_at).build());
+        assert!(contract.vote_proposal(proposal_id, true));
+        // 提案後才取得的聲望不計入票數
+        testing_env!(context("carol.near").block_timestamp(created_at).build());
+        contract.set_karma(&account("carol.near"), 2_000);
+        assert!(contract.vote_proposal(proposal_id, false));
+        let proposal = contract.proposals.get(&proposal_id).unwrap();
+        assert_eq!(proposal.quorum, governance.karma_quorum);
+        assert_eq!((proposal.yes, proposal.no), (800, 400));
+        // 投票期間還沒結束，不會執行
+        assert!(
+            contract.finalize_proposal(proposal_id).unwrap().status == ProposalStatus::InProgress
+        );
+        assert!(!contract.is_moderator(&account("dave.near")));
+        // 投票期間結束後不能再投票，通過的提案會被執行
+        testing_env!(context("carol.near")
+            .block_timestamp(created_at + governance.voting_period)
+            .build());
+        assert!(!contract.vote_proposal(proposal_id, true));
+        assert!(contract.proposals.get(&proposal_id).unwrap().status == ProposalStatus::Approved);
+        assert!(contract.is_moderator(&account("dave.near")));
     }
 
-    // 取消點讚
-    pub fn unlike_post(&mut self, post_id: u128) -> WithStatus<Post> {
-        self.posts
-            .to_vec()
-            .into_iter()
-            .map(
-                |(id, post_with_status)| match (id == post_id, post_with_status) {
-                    // 僅有狀態為開放的文章可以被取消點讚
-                    (true, Open(mut post)) => {
-                        // 嘗試找出存放自己使用者ID的index
-                        match &post
-                            .users_who_liked
-                            .clone()
-                            .into_iter()
-                            .position(|user_id| user_id == env::signer_account_id())
-                        {
-                            // 找不到自己的使用者ID，回傳
-                            None => Empty,
-                            // 找到使用者ID的index，繼續操作
-                            Some(index) => {
-                                // 將自己的使用者ID，透過index移除
-                                post.users_who_liked.remove(*index);
-                                // 儲存改好的文章
-                                self.posts.insert(&id, &Open(post.clone()));
-                                // 回傳文章
-                                Open(post)
-                            }
-                        }
-                    }
-                    // 找不到文章，回傳
-                    _ => Empty,
+    #[test]
+    fn only_eligible_voters_count_toward_quorum() {
+        let mut contract = new_contract();
+        contract.config.governance.voting_mode = VotingMode::ByAccount;
+        let governance = contract.config.governance.clone();
+        add_voter(&mut contract, "alice.near", 20);
+        let created_at = governance.min_account_age;
+        // 帳號夠久，但聲望是在提案前不久才取得的
+        add_post(&mut contract, "dave.near");
+        testing_env!(context("dave.near")
+            .block_timestamp(created_at - KARMA_CHECKPOINT_INTERVAL)
+            .build());
+        contract.set_karma(&account("dave.near"), 100);
+        // 聲望不夠的提案者不能提案
+        testing_env!(context("bob.near").build());
+        assert!(contract
+            .create_proposal(
+                ProposalAction::RemoveModerator {
+                    account_id: account("alice.near"),
+                },
+                "remove alice".to_string(),
+            )
+            .is_none());
+        testing_env!(context("alice.near").block_timestamp(created_at).build());
+        // 變更管理員名單的提案一律依聲望計票，看板可以每個帳號一票
+        let privileged = contract
+            .create_proposal(
+                ProposalAction::AddModerator {
+                    account_id: account("bob.near"),
+                },
+                "add bob".to_string(),
+            )
+            .unwrap();
+        let board = contract
+            .create_proposal(
+                ProposalAction::CreateBoard {
+                    name: "rust".to_string(),
+                    description: "Rust".to_string(),
+                    restricted: false,
+                    auto_lock_after: None,
                 },
+                "create a board".to_string(),
             )
-            // 只回傳一個（邏輯上一個post_id對應到的就只會有一個）
-            .next()
-            // 如果一個都找不到，就回傳無
-            .unwrap_or(Empty)
+            .unwrap();
+        assert!(contract.proposals.get(&privileged).unwrap().voting_mode == VotingMode::ByKarma);
+        assert!(contract.proposals.get(&board).unwrap().voting_mode == VotingMode::ByAccount);
+        assert_eq!(
+            contract.proposals.get(&board).unwrap().quorum,
+            governance.account_quorum
+        );
+        assert!(contract.vote_proposal(board, true));
+        // 提案後才開始活動的帳號，或沒有聲望的帳號都不能投票
+        (0..5).for_each(|index| {
+            let sybil = format!("sybil{}.near", index);
+            testing_env!(context(&sybil).block_timestamp(created_at).build());
+            contract.add_comment(0, None, "comment".to_string(), None);
+            contract.set_karma(&account(&sybil), 1);
+            assert!(!contract.vote_proposal(board, true));
+        });
+        testing_env!(context("bob.near").block_timestamp(created_at).build());
+        assert!(!contract.vote_proposal(board, true));
+        testing_env!(context("dave.near").block_timestamp(created_at).build());
+        assert!(!contract.vote_proposal(board, true));
+        // 投票數沒有達到門檻，提案不通過
+        testing_env!(context("alice.near")
+            .block_timestamp(created_at + governance.voting_period)
+            .build());
+        assert!(contract.finalize_proposal(board).unwrap().status == ProposalStatus::Rejected);
+        assert!(contract.get_boards().is_empty());
     }
 
-    // 編輯文章（只有原作者可以修改或移除文章）
-    pub fn edit_post(
-        &mut self,
-        post_id: u128,
-        title: Option<String>,
-        content: Option<String>,
-        tags: Option<Vec<String>>,
-        status: String,
-    ) -> WithStatus<Post> {
-        // 嘗試找出文章
-        match self.posts.get(&post_id) {
-            // 找不到，回傳無
-            None => Empty,
-            // 找到，繼續操作
-            Some(original_post_with_status) => {
-                // 判斷找到的文章的狀態，以及確認status是否正確
-                match (
-                    original_post_with_status,
-                    Self::check_status_string(&status),
-                ) {
-                    // 開放，可以做任何操作
-                    (Open(original_post), true) => {
-                        // 確認身份，只有原作者可以修改
-                        if original_post.creator_user_id == env::signer_account_id() {
-                            // 製作新的文章，有指定參數的才改
-                            // 沒有指定參數的欄位（JSON填null），把原本文章的資訊填回去
-                            let edited_post_with_status = WithStatus::new_with_status_string(
-                                Post {
-                                    title: title.unwrap_or(original_post.title),
-                                    content: content.unwrap_or(original_post.content),
-                                    tags: tags.unwrap_or(original_post.tags),
-                                    ..original_post
-                                },
-                                status,
-                            );
-                            // 儲存修改過的文章
-                            self.posts.insert(&post_id, &edited_post_with_status);
-                            // 回傳
-                            edited_post_with_status
-                        } else {
-                            // 身份不對，直接回傳無
-                            Empty
-                        }
-                    }
-                    // 鎖定，只能移除
-                    (Locked(original_post), true) => {
-                        // 確認身份，只有原作者可以修改 & 確認是要移除文章
-                        if original_post.creator_user_id == env::signer_account_id()
-                            && status == "Removed"
-                        {
-                            let edited_post_with_status =
-                                WithStatus::new_with_status_string(original_post, status);
-                            // 儲存修改過的文章
-                            self.posts.insert(&post_id, &edited_post_with_status);
-                            // 回傳
-                            edited_post_with_status
-                        } else {
-                            // 身份不對或不是要移除文章，直接回傳無
-                            Empty
-                        }
-                    }
-                    // 其他狀態或者status有問題都不能異動
-                    _ => Empty,
-                }
-            }
-        }
+    #[test]
+    fn merged_tags_stay_merged_after_restore() {
+        let mut contract = new_contract();
+        testing_env!(context("alice.near").build());
+        let post_id = match contract.add_post(
+            "title".to_string(),
+            "content".to_string(),
+            vec!["old".to_string()],
+            None,
+        ) {
+            Open(post) => post.id,
+            _ => panic!("add_post failed"),
+        };
+        contract.edit_post(post_id, None, None, None, "Removed".to_string());
+        testing_env!(context(CONTRACT).build());
+        assert!(contract.merge_tags(vec!["old".to_string()], "new".to_string()));
+        // 被移除的文章不在索引中，但標籤還是要改
+        assert!(contract.get_tags(0, 10).is_empty());
+        testing_env!(context("alice.near").build());
+        assert!(matches!(contract.restore_post(post_id), Open(post) if post.tags == vec!["new"]));
+        assert_eq!(contract.get_tags(0, 10), vec![("new".to_string(), 1)]);
     }
 
-    // 新增留言
-    pub fn add_comment(
-        &mut self,
-        post_id: u128,
-        comment_id: Option<u128>,
-        content: String,
-    ) -> WithStatus<Post> {
-        // 找出文章，並確認要新增的留言是否是子留言
-        match (self.posts.get(&post_id), comment_id) {
-            // 有找到文章，而且要新增子留言
-            (Some(Open(mut post)), Some(comment_id)) => {
-                // 把留言撈出來
-                match &post.comments[comment_id as usize] {
-                    // 留言是開放的
-                    Open(comment) => {
-                        // 複製一份留言
-                        let mut new_comment = comment.clone();
-                        // 把新的子留言推進去
-                        new_comment.sub_comments.push(Open(SubComment {
-                            content,
-                            ..SubComment::default()
-                        }));
-                        // 把舊的留言刪除
-                        post.comments.remove(comment_id as usize);
-                        // 把新的留言推到文章中
-                        post.comments.push(Open(new_comment));
-                        // 儲存更改後的文章
-                        self.posts.insert(&post_id, &Open(post.clone()));
-                        // 回傳文章
-                        Open(post)
-                    }
-                    // 留言不是開放的或沒找到
-                    _ => Empty,
-                }
-            }
-            // 有找到文章，但不是要新增子留言
-            (Some(Open(mut post)), None) => {
-                // 直接把新留言推到文章裡
-                post.comments.push(Open(Comment {
-                    content,
-                    ..Comment::default()
-                }));
-                // 儲存更改後的文章
-                self.posts.insert(&post_id, &Open(post.clone()));
-                // 回傳文章
-                Open(post)
-            }
-            // 找不到文章
-            _ => Empty,
+    #[test]
+    fn migrate_converts_old_posts() {
+        testing_env!(context(CONTRACT).build());
+        let mut old = OldBulletinBoard {
+            posts: UnorderedMap::new(b'm'),
+            tags: UnorderedMap::new(b'n'),
+            number_of_posts: 3,
+            likes_by_user_id: UnorderedMap::new(b'o'),
+        };
+        old.posts.insert(
+            &0,
+            &Open(OldPost {
+                id: 0,
+                title: "title".to_string(),
+                content: "content".to_string(),
+                tags: vec!["rust".to_string()],
+                users_who_liked: vec![account("bob.near")],
+                creator_user_id: account("alice.near"),
+                comments: vec![Open(OldComment {
+                    comment_creator_user_id: account("bob.near"),
+                    content: "comment".to_string(),
+                    users_who_liked: vec![account("alice.near"), account("carol.near")],
+                    sub_comments: vec![Removed(OldSubComment {
+                        comment_creator_user_id: account("alice.near"),
+                        content: "reply".to_string(),
+                        users_who_liked: Vec::new(),
+                    })],
+                })],
+            }),
+        );
+        // 舊版合約沒有正規化標籤，而且標籤索引只記錄了第一篇文章
+        [(1, "Rust ", false), (2, "rust", true)]
+            .into_iter()
+            .for_each(|(post_id, tag, removed)| {
+                let old_post = OldPost {
+                    id: post_id,
+                    title: "title".to_string(),
+                    content: "content".to_string(),
+                    tags: vec![tag.to_string(), "#invalid".to_string()],
+                    users_who_liked: Vec::new(),
+                    creator_user_id: account("alice.near"),
+                    comments: Vec::new(),
+                };
+                old.posts.insert(
+                    &post_id,
+                    &if removed {
+                        Removed(old_post)
+                    } else {
+                        Open(old_post)
+                    },
+                );
+            });
+        old.tags.insert(&"rust".to_string(), &vec![0]);
+        old.tags.insert(&"Rust ".to_string(), &vec![1]);
+        env::state_write(&old);
+
+        let mut board = BulletinBoard::migrate();
+        let post = match board.posts.get(&0) {
+            Some(Open(post)) => post,
+            _ => panic!("post was not migrated as open"),
+        };
+        assert_eq!(post.title, "title");
+        assert_eq!(post.comments.len(), 2);
+        // 子留言變成回覆原本留言的留言，狀態維持不變
+        assert!(matches!(&post.comments[0], Open(comment) if comment.id == 0));
+        assert!(matches!(
+            &post.comments[1],
+            Removed(reply) if reply.id == 1 && reply.parent_id == Some(0) && reply.depth == 1
+        ));
+        assert_eq!(post.created_at, UNKNOWN_TIMESTAMP);
+        // 標籤索引從正規化後的標籤重建，被移除的文章不在索引中
+        assert_eq!(board.get_tags(0, 10), vec![("rust".to_string(), 2)]);
+        assert_eq!(
+            board
+                .search_post_by_tags(vec!["Rust".to_string()])
+                .into_iter()
+                .map(|(post_id, _)| post_id)
+                .collect::<Vec<u128>>(),
+            vec![0, 1]
+        );
+        assert_eq!(board.number_of_posts, 3);
+        assert_eq!(
+            board.posts_by_author.get(&account("alice.near")),
+            Some(vec![0, 1, 2])
+        );
+        assert_eq!(board.karma_of(&account("alice.near")), 1);
+        assert_eq!(board.karma_of(&account("bob.near")), 2);
+        // 舊版合約移除的文章不知道移除時間，作者還是可以復原
+        testing_env!(context("alice.near").block_timestamp(1_000_000_000).build());
+        assert!(matches!(board.restore_post(2), Open(post) if post.tags == vec!["rust"]));
+        assert_eq!(board.get_tags(0, 10), vec![("rust".to_string(), 3)]);
+    }
+
+    #[test]
+    fn net_score_above_the_threshold_earns_the_badge() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        (0..=EXPLOSIVE_SCORE).for_each(|index| {
+            testing_env!(context(&format!("user{}.near", index)).build());
+            assert!(matches!(
+                contract.react_post(post_id, Some(Reaction::Push)),
+                Open(_)
+            ));
+        });
+        // 同樣的推文不會重複計算
+        assert!(matches!(
+            contract.react_post(post_id, Some(Reaction::Push)),
+            Empty
+        ));
+        let summary = contract.get_post_summaries(0, 1).pop().unwrap();
+        assert_eq!(summary.net_score, EXPLOSIVE_SCORE + 1);
+        assert_eq!(summary.badge.as_deref(), Some("爆"));
+        // 改成噓會扣掉原本的推
+        assert!(matches!(
+            contract.react_post(post_id, Some(Reaction::Boo)),
+            Open(post) if (post.reaction_tally.push, post.reaction_tally.boo) == (100, 1)
+        ));
+        let summary = contract.get_post_summaries(0, 1).pop().unwrap();
+        assert_eq!(summary.net_score, EXPLOSIVE_SCORE - 1);
+        assert!(summary.badge.is_none());
+        // 收回推文後淨推文數剛好等於門檻，還不會爆
+        assert!(matches!(contract.react_post(post_id, None), Open(_)));
+        assert!(matches!(contract.react_post(post_id, None), Empty));
+        let summary = contract.get_post_summaries(0, 1).pop().unwrap();
+        assert_eq!(summary.net_score, EXPLOSIVE_SCORE);
+        assert!(summary.badge.is_none());
+    }
+
+    #[test]
+    fn bans_apply_to_their_board_until_they_expire() {
+        let mut contract = new_contract();
+        let rust = Some("rust".to_string());
+        // 一般帳號不能封鎖別人，合約擁有者也不能被封鎖
+        testing_env!(context("carol.near").build());
+        assert!(!contract.ban_account(account("alice.near"), None, "spam".to_string(), None));
+        testing_env!(context(CONTRACT).build());
+        assert!(!contract.ban_account(account(CONTRACT), None, "spam".to_string(), None));
+        assert!(contract.ban_account(
+            account("alice.near"),
+            rust.clone(),
+            "off-topic".to_string(),
+            None
+        ));
+        assert!(contract.ban_account(account("bob.near"), None, "spam".to_string(), Some(1_000)));
+        // 看板封鎖只在那個看板有效，全站封鎖在所有看板都有效（寫入時用同樣的方式檢查）
+        assert!(contract
+            .active_ban(&account("alice.near"), rust.as_ref())
+            .is_some());
+        assert!(!contract.is_banned(account("alice.near"), None));
+        assert!(!contract.is_banned(account("alice.near"), Some("go".to_string())));
+        assert!(contract.is_banned(account("bob.near"), None));
+        assert!(contract.is_banned(account("bob.near"), rust.clone()));
+        assert_eq!(contract.get_bans(0, 10).len(), 2);
+        // 被看板封鎖的帳號還是可以在看板以外發文
+        add_post(&mut contract, "alice.near");
+        // 到期後封鎖失效
+        testing_env!(context("bob.near").block_timestamp(1_000).build());
+        assert!(contract.active_ban(&account("bob.near"), None).is_none());
+        assert!(matches!(
+            contract.add_post("title".to_string(), "content".to_string(), Vec::new(), None),
+            Open(_)
+        ));
+        assert_eq!(contract.get_bans(0, 10).len(), 1);
+        // 解除封鎖
+        testing_env!(context(CONTRACT).block_timestamp(1_000).build());
+        assert!(contract.unban_account(account("alice.near"), rust.clone()));
+        assert!(!contract.unban_account(account("alice.near"), rust.clone()));
+        assert!(!contract.is_banned(account("alice.near"), rust));
+    }
+
+    #[test]
+    fn posts_auto_lock_and_unlocking_restarts_the_timer() {
+        let mut contract = new_contract();
+        let hour: u64 = 60 * 60 * 1_000_000_000;
+        assert!(contract.create_board("news".to_string(), "News".to_string(), false, Some(hour)));
+        testing_env!(context("alice.near").block_timestamp(hour).build());
+        let post_id = match contract.add_post(
+            "title".to_string(),
+            "content".to_string(),
+            Vec::new(),
+            Some(PostOptions {
+                board: Some("news".to_string()),
+                ..PostOptions::default()
+            }),
+        ) {
+            Open(post) => post.id,
+            _ => panic!("add_post failed"),
+        };
+        testing_env!(context("alice.near").block_timestamp(2 * hour - 1).build());
+        assert!(matches!(contract.get_post(post_id, None), Open(_)));
+        // 開放的文章不能解除鎖定
+        assert!(matches!(contract.unlock_post(post_id), Empty));
+        // 超過看板的期限就自動鎖定，不能再留言
+        testing_env!(context("bob.near").block_timestamp(2 * hour).build());
+        assert!(matches!(contract.get_post(post_id, None), Locked(_)));
+        assert!(matches!(
+            contract.add_comment(post_id, None, "comment".to_string(), None),
+            Empty
+        ));
+        // 只有原作者或管理員可以解除鎖定
+        assert!(matches!(contract.unlock_post(post_id), Empty));
+        testing_env!(context("alice.near").block_timestamp(3 * hour).build());
+        assert!(matches!(contract.unlock_post(post_id), Open(_)));
+        // 從解除鎖定的時間重新計算
+        testing_env!(context("alice.near").block_timestamp(4 * hour - 1).build());
+        assert!(matches!(contract.get_post(post_id, None), Open(_)));
+        testing_env!(context("alice.near").block_timestamp(4 * hour).build());
+        assert!(matches!(contract.get_post(post_id, None), Locked(_)));
+    }
+
+    #[test]
+    fn threads_are_listed_depth_first() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        let first = add_comment(&mut contract, "bob.near", post_id, None);
+        let second = add_comment(&mut contract, "carol.near", post_id, None);
+        let reply = add_comment(&mut contract, "dave.near", post_id, Some(first));
+        let nested = add_comment(&mut contract, "eve.near", post_id, Some(reply));
+        let other = add_comment(&mut contract, "frank.near", post_id, Some(second));
+        let ids = |comments: Vec<WithStatus<Comment>>| -> Vec<u128> {
+            comments
+                .iter()
+                .map(|comment| comment.inner().unwrap().id)
+                .collect()
+        };
+        // 回覆緊接在被回覆的留言之後
+        assert_eq!(
+            ids(contract.get_thread(post_id, 0, 10)),
+            vec![first, reply, nested, second, other]
+        );
+        assert_eq!(ids(contract.get_thread(post_id, 1, 2)), vec![reply, nested]);
+        assert_eq!(
+            ids(contract.get_replies(post_id, Some(first), 0, 10)),
+            vec![reply]
+        );
+        match contract.get_thread(post_id, 2, 1).pop() {
+            Some(Open(comment)) => assert_eq!(comment.depth, 2),
+            _ => panic!("nested reply missing"),
         }
+        // 被移除的留言不能再回覆，也不會顯示，但它底下的回覆仍然會顯示
+        testing_env!(context("carol.near").build());
+        assert!(matches!(
+            contract.edit_comment(post_id, second, String::new(), Some("Removed".to_string())),
+            Open(_)
+        ));
+        testing_env!(context("alice.near").build());
+        assert!(matches!(
+            contract.add_comment(post_id, Some(second), "comment".to_string(), None),
+            Empty
+        ));
+        assert_eq!(
+            ids(contract.get_thread(post_id, 0, 10)),
+            vec![first, reply, nested, other]
+        );
     }
 
-    // 編輯留言
-    pub fn edit_comment(
-        &mut self,
-        post_id: u128,
-        comment_id: u128,
-        sub_comment_id: Option<u128>,
-        content: String,
-        status: Option<String>,
-    ) -> WithStatus<Post> {
-        // 撈出文章，並確認是否修改子留言/狀態
-        // 文章鎖定後不能修改其下的留言，留言鎖定後不能修改其下的子留言
-        match (self.posts.get(&post_id), sub_comment_id, status) {
-            // 開放文章，留言內容
-            // 先檢查留言是否存在
-            (Some(Open(mut post)), None, None) => match &post.comments[comment_id as usize] {
-                // 留言存在，且是開放狀態
-                Open(comment) => {
-                    // 複製舊的留言
-                    let mut new_comment = comment.clone();
-                    // 替換新留言的內容
-                    new_comment.content = content;
-                    // 移除舊留言
-                    post.comments.remove(comment_id as usize);
-                    // 將新留言推進文章中
-                    post.comments.push(Open(new_comment));
-                    // 儲存文章
-                    self.posts.insert(&post_id, &Open(post.clone()));
-                    // 回傳
-                    Open(post)
-                }
-                // 找不到留言，回傳無
-                _ => Empty,
-            },
-            // 開放文章，子留言內容
-            (Some(Open(mut post)), Some(sub_comment_id), None) => {
-                // 先檢查留言是否存在
-                match &post.comments[comment_id as usize] {
-                    // 留言存在，且是開放狀態
-                    Open(comment) => {
-                        // 複製舊的留言
-                        let mut new_comment = comment.clone();
-                        // 如果子留言是開放狀態
-                        if let Open(sub_comment) =
-                            &new_comment.sub_comments[sub_comment_id as usize]
-                        {
-                            // 複製舊的子留言
-                            let mut new_sub_comment = sub_comment.clone();
-                            // 替換新子留言的內容
-                            new_sub_comment.content = content;
-                            // 移除舊子留言
-                            new_comment.sub_comments.remove(sub_comment_id as usize);
-                            // 將新子留言推進留言中
-                            new_comment.sub_comments.push(Open(new_sub_comment));
-                        }
-                        // 移除舊留言
-                        post.comments.remove(comment_id as usize);
-                        // 將新留言推進文章中
-                        post.comments.push(Open(new_comment));
-                        // 儲存文章
-                        self.posts.insert(&post_id, &Open(post.clone()));
-                        // 回傳
-                        Open(post)
-                    }
-                    // 找不到留言，回傳無
-                    _ => Empty,
-                }
-            }
-            // 開放文章，留言狀態
-            (Some(Open(mut post)), None, Some(status)) => {
-                // 先檢查留言是否存在
-                match &post.comments[comment_id as usize] {
-                    // 留言存在，且是開放狀態
-                    Open(comment) => {
-                        // 確認請求提供的留言狀態正常
-                        if Self::check_status_string(&status) {
-                            // 複製舊的留言
-                            let new_comment = comment.clone();
-                            // 移除舊留言
-                            post.comments.remove(comment_id as usize);
-                            // 將舊留言加入新的狀態一併推進文章中
-                            post.comments
-                                .push(WithStatus::new_with_status_string(new_comment, status));
-                            // 儲存文章
-                            self.posts.insert(&post_id, &Open(post.clone()));
-                            // 回傳
-                            Open(post)
-                        } else {
-                            // 留言狀態不正常，回傳無
-                            Empty
-                        }
-                    }
-                    // 留言存在，且是鎖定狀態（僅能移除）
-                    Locked(comment) => {
-                        // 確認請求提供的留言狀態正常，且要求的狀態是移除
-                        if Self::check_status_string(&status) && status == "Removed" {
-                            // 複製舊的留言
-                            let new_comment = comment.clone();
-                            // 移除舊留言
-                            post.comments.remove(comment_id as usize);
-                            // 將舊留言加入新的狀態一併推進文章中
-                            post.comments
-                                .push(WithStatus::new_with_status_string(new_comment, status));
-                            // 儲存文章
-                            self.posts.insert(&post_id, &Open(post.clone()));
-                            // 回傳
-                            Open(post)
-                        } else {
-                            // 留言狀態不正常，回傳無
-                            Empty
-                        }
-                    }
-                    // 留言不存在，回傳無
-                    _ => Empty,
-                }
-            }
-            // 開放文章，子留言狀態
-            (Some(Open(mut post)), Some(sub_comment_id), Some(status)) => {
-                // 先檢查留言是否存在
-                match &post.comments[comment_id as usize] {
-                    // 留言存在，且是開放狀態
-                    Open(comment) => {
-                        // 複製舊的留言
-                        let mut new_comment = comment.clone();
-                        // 檢查子留言是否存在
-                        match &new_comment.sub_comments[sub_comment_id as usize] {
-                            // 子留言存在，且是開放狀態
-                            Open(sub_comment) => {
-                                // 確認請求提供的留言狀態正常
-                                if Self::check_status_string(&status) {
-                                    // 複製舊的子留言
-                                    let new_sub_comment = sub_comment.clone();
-                                    // 移除舊的子留言
-                                    new_comment.sub_comments.remove(sub_comment_id as usize);
-                                    // 將舊子留言加入新的狀態一併推進留言中
-                                    new_comment.sub_comments.push(
-                                        WithStatus::new_with_status_string(new_sub_comment, status),
-                                    );
-                                    // 移除舊的留言
-                                    post.comments.remove(comment_id as usize);
-                                    // 將新留言推進文章中
-                                    post.comments.push(Open(new_comment));
-                                    // 儲存文章
-                                    self.posts.insert(&post_id, &Open(post.clone()));
-                                    // 回傳
-                                    Open(post)
-                                } else {
-                                    // 留言狀態不正常，回傳無
-                                    Empty
-                                }
-                            }
-                            // 子留言存在，且是鎖定狀態（僅能移除）
-                            Locked(sub_comment) => {
-                                // 確認請求提供的留言狀態正常，且要求的狀態是移除
-                                if Self::check_status_string(&status) && status == "Removed" {
-                                    // 複製舊的子留言
-                                    let new_sub_comment = sub_comment.clone();
-                                    // 移除舊的子留言
-                                    new_comment.sub_comments.remove(sub_comment_id as usize);
-                                    // 將舊子留言加入新的狀態一併推進留言中
-                                    new_comment.sub_comments.push(
-                                        WithStatus::new_with_status_string(new_sub_comment, status),
-                                    );
-                                    // 移除舊的留言
-                                    post.comments.remove(comment_id as usize);
-                                    // 將新留言推進文章中
-                                    post.comments.push(Open(new_comment));
-                                    // 儲存文章
-                                    self.posts.insert(&post_id, &Open(post.clone()));
-                                    // 回傳
-                                    Open(post)
-                                } else {
-                                    // 留言狀態不正常，回傳無
-                                    Empty
-                                }
-                            }
-                            // 子留言不存在，回傳無
-                            _ => Empty,
-                        }
-                    }
-                    // 留言不存在，回傳無
-                    _ => Empty,
-                }
-            }
-            // 不符合以上條件，一律回傳無
-            (_, _, _) => Empty,
+    #[test]
+    fn mentions_skip_punctuation_duplicates_and_invalid_accounts() {
+        let contract = new_contract();
+        assert!(
+            contract.parse_mentions(
+                "hi @bob.near, @bob.near again (@carol.near) email@dave.near @Eve.near @x @frank.near."
+            ) == vec![account("bob.near"), account("frank.near")]
+        );
+        assert!(contract.parse_mentions("no mentions @ here").is_empty());
+    }
+
+    #[test]
+    fn mentions_and_replies_notify_until_read() {
+        let mut contract = new_contract();
+        let post_id = add_post(&mut contract, "alice.near");
+        // 被回覆的作者只收到回覆通知，提及自己不會收到通知
+        testing_env!(context("bob.near").build());
+        contract.add_comment(
+            post_id,
+            None,
+            "@carol.near @alice.near @bob.near".to_string(),
+            None,
+        );
+        testing_env!(context("dave.near").build());
+        contract.add_comment(post_id, None, "@carol.near".to_string(), None);
+        let alice = contract.get_notifications(account("alice.near"), 0, 10);
+        assert_eq!(alice.len(), 2);
+        assert!(alice
+            .iter()
+            .all(|notification| matches!(notification.kind, NotificationKind::Reply)));
+        let carol = contract.get_notifications(account("carol.near"), 0, 10);
+        assert_eq!(carol.len(), 2);
+        assert!(carol[1].from == account("bob.near"));
+        assert!(matches!(carol[1].kind, NotificationKind::Mention));
+        assert!(contract
+            .get_notifications(account("bob.near"), 0, 10)
+            .is_empty());
+        // 標記已讀，不能重複標記，也不能把已讀改回未讀
+        testing_env!(context("carol.near").build());
+        assert!(contract.mark_notifications_read(1));
+        assert!(!contract.mark_notifications_read(1));
+        let carol = contract.get_notifications(account("carol.near"), 0, 10);
+        assert!(!carol[0].read && carol[1].read);
+        assert!(contract.mark_notifications_read(100));
+        assert!(!contract.mark_notifications_read(100));
+        assert!(contract
+            .get_notifications(account("carol.near"), 0, 10)
+            .iter()
+            .all(|notification| notification.read));
+        // 沒有收件匣的帳號
+        testing_env!(context("bob.near").build());
+        assert!(!contract.mark_notifications_read(1));
+    }
+
+    // 以指定帳號發表附加投票的文章，回傳文章流水號
+    fn add_poll_post(
+        contract: &mut BulletinBoard,
+        account_id: &str,
+        multiple_choice: bool,
+        voters_visible: bool,
+    ) -> u128 {
+        testing_env!(context(account_id).build());
+        let options = PostOptions {
+            poll: Some(NewPoll {
+                options: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                multiple_choice,
+                ends_at: 1_000,
+                voters_visible,
+            }),
+            ..PostOptions::default()
+        };
+        match contract.add_post(
+            "title".to_string(),
+            "content".to_string(),
+            Vec::new(),
+            Some(options),
+        ) {
+            Open(post) => post.id,
+            _ => panic!("add_post failed"),
         }
     }
+
+    #[test]
+    fn polls_count_one_vote_per_account_until_they_close() {
+        let mut contract = new_contract();
+        let post_id = add_poll_post(&mut contract, "alice.near", false, false);
+        testing_env!(context("bob.near").build());
+        assert!(contract.vote_poll(post_id, vec![1]));
+        // 不能重複投票
+        assert!(!contract.vote_poll(post_id, vec![2]));
+        // 單選不能選多個，也不能選不存在的選項或不選
+        testing_env!(context("carol.near").build());
+        assert!(!contract.vote_poll(post_id, vec![0, 1]));
+        assert!(!contract.vote_poll(post_id, vec![5]));
+        assert!(!contract.vote_poll(post_id, Vec::new()));
+        assert!(contract.vote_poll(post_id, vec![0]));
+        let results = contract.get_poll_results(post_id).unwrap();
+        assert_eq!(results.counts, vec![1, 1, 0]);
+        assert_eq!(results.number_of_voters, 2);
+        assert!(!results.closed);
+        assert!(results.voters.is_none());
+        // 結束後就不能再投票
+        testing_env!(context("dave.near").block_timestamp(1_000).build());
+        assert!(!contract.vote_poll(post_id, vec![2]));
+        let results = contract.get_poll_results(post_id).unwrap();
+        assert!(results.closed);
+        assert_eq!(results.counts, vec![1, 1, 0]);
+        // 沒有投票的文章
+        let plain_post_id = add_post(&mut contract, "eve.near");
+        assert!(contract.get_poll_results(plain_post_id).is_none());
+    }
+
+    #[test]
+    fn multiple_choice_polls_ignore_repeated_choices() {
+        let mut contract = new_contract();
+        let post_id = add_poll_post(&mut contract, "alice.near", true, true);
+        testing_env!(context("bob.near").build());
+        assert!(contract.vote_poll(post_id, vec![2, 0, 2]));
+        testing_env!(context("carol.near").build());
+        assert!(!contract.vote_poll(post_id, vec![0, 3]));
+        assert!(contract.vote_poll(post_id, vec![0]));
+        let results = contract.get_poll_results(post_id).unwrap();
+        assert_eq!(results.counts, vec![2, 0, 1]);
+        assert!(
+            results.voters
+                == Some(vec![
+                    (account("bob.near"), vec![0, 2]),
+                    (account("carol.near"), vec![0]),
+                ])
+        );
+    }
+
+    #[test]
+    fn token_gated_boards_accept_verified_members_until_the_ttl() {
+        let mut contract = new_contract();
+        let gate = BoardGate::Ft {
+            contract_id: account("token.near"),
+            min_balance: U128(100),
+        };
+        assert!(contract.create_board("vip".to_string(), "VIP".to_string(), false, None));
+        assert!(contract.create_board("open".to_string(), "Open".to_string(), false, None));
+        // 只有管理員可以設定持有資格
+        testing_env!(context("alice.near").build());
+        assert!(!contract.set_board_gate("vip".to_string(), Some(gate.clone())));
+        testing_env!(context(CONTRACT).build());
+        assert!(contract.set_board_gate("vip".to_string(), Some(gate)));
+        // 餘額不足、查詢失敗或看板沒有設定持有資格都不算通過
+        callback_context(PromiseResult::Successful(b"\"50\"".to_vec()));
+        assert!(!contract.on_membership_checked(account("alice.near"), "vip".to_string()));
+        callback_context(PromiseResult::Failed);
+        assert!(!contract.on_membership_checked(account("alice.near"), "vip".to_string()));
+        callback_context(PromiseResult::Successful(b"\"150\"".to_vec()));
+        assert!(!contract.on_membership_checked(account("alice.near"), "open".to_string()));
+        assert!(contract
+            .get_membership(account("alice.near"), "vip".to_string())
+            .is_none());
+        // 餘額足夠就記錄有效期限，期間內可以在看板發文
+        assert!(contract.on_membership_checked(account("alice.near"), "vip".to_string()));
+        let ttl = contract.config.membership_ttl;
+        assert_eq!(
+            contract.get_membership(account("alice.near"), "vip".to_string()),
+            Some(ttl)
+        );
+        testing_env!(context("alice.near").block_timestamp(ttl - 1).build());
+        let options = PostOptions {
+            board: Some("vip".to_string()),
+            ..PostOptions::default()
+        };
+        assert!(matches!(
+            contract.add_post(
+                "title".to_string(),
+                "content".to_string(),
+                Vec::new(),
+                Some(options)
+            ),
+            Open(_)
+        ));
+        // 過期後要重新確認
+        testing_env!(context("alice.near").block_timestamp(ttl).build());
+        assert!(contract
+            .get_membership(account("alice.near"), "vip".to_string())
+            .is_none());
+    }
 }
@@ -0,0 +1,26 @@
+// 整合測試用的NEP-141代幣合約
+use near_contract_standards::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault, PromiseOrValue};
+
+#[near_bindgen]
+#[derive(BorshSerialize, BorshDeserialize, PanicOnDefault)]
+pub struct MockFt {
+    token: FungibleToken,
+}
+
+#[near_bindgen]
+impl MockFt {
+    // 發行代幣，全部給owner_id
+    #[init]
+    pub fn new(owner_id: AccountId, total_supply: U128) -> Self {
+        let mut token = FungibleToken::new(b"t".to_vec());
+        token.internal_register_account(&owner_id);
+        token.internal_deposit(&owner_id, total_supply.0);
+        Self { token }
+    }
+}
+
+near_contract_standards::impl_fungible_token_core!(MockFt, token);
+near_contract_standards::impl_fungible_token_storage!(MockFt, token);
@@ -1,16 +1,20 @@
 use std::{env, fs};
 use near_units::parse_near;
 use serde_json::json;
-use workspaces::{Account, Contract};
+use workspaces::{Account, AccountId, Contract};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let wasm_arg: &str = &(env::args().nth(1).unwrap());
     let wasm_filepath = fs::canonicalize(env::current_dir()?.join(wasm_arg))?;
+    let ft_wasm_arg: &str = &(env::args().nth(2).unwrap());
+    let ft_wasm_filepath = fs::canonicalize(env::current_dir()?.join(ft_wasm_arg))?;
 
     let worker = workspaces::sandbox().await?;
     let wasm = std::fs::read(wasm_filepath)?;
     let contract = worker.dev_deploy(&wasm).await?;
+    let ft_wasm = std::fs::read(ft_wasm_filepath)?;
+    let ft = worker.dev_deploy(&ft_wasm).await?;
 
     // create accounts
     let account = worker.dev_create_account().await?;
@@ -20,47 +24,213 @@ async fn main() -> anyhow::Result<()> {
         .transact()
         .await?
         .into_result()?;
+    let bob = account
+        .create_subaccount( "bob")
+        .initial_balance(parse_near!("30 N"))
+        .transact()
+        .await?
+        .into_result()?;
 
     // begin tests
-    test_default_message(&alice, &contract).await?;
-    test_changes_message(&alice, &contract).await?;
+    test_ft_tipping(&alice, &bob, &contract, &ft).await?;
+    test_premium_posts(&alice, &bob, &contract).await?;
     Ok(())
 }
 
-async fn test_default_message(
+async fn ft_balance_of(
     user: &Account,
+    ft: &Contract,
+    account_id: &AccountId,
+) -> anyhow::Result<String> {
+    let balance: String = user
+        .call(ft.id(), "ft_balance_of")
+        .args_json(json!({"account_id": account_id}))
+        .transact()
+        .await?
+        .json()?;
+    Ok(balance)
+}
+
+async fn test_ft_tipping(
+    alice: &Account,
+    bob: &Account,
     contract: &Contract,
+    ft: &Contract,
 ) -> anyhow::Result<()> {
-    let message: String = user
-        .call( contract.id(), "get_greeting")
-        .args_json(json!({}))
+    // 發行代幣，並在代幣合約註冊alice、bob與看板合約
+    ft.as_account()
+        .call(ft.id(), "new")
+        .args_json(json!({"owner_id": ft.id(), "total_supply": "1000"}))
+        .transact()
+        .await?
+        .into_result()?;
+    for account_id in [alice.id(), bob.id(), contract.id()] {
+        ft.as_account()
+            .call(ft.id(), "storage_deposit")
+            .args_json(json!({"account_id": account_id}))
+            .deposit(parse_near!("0.01 N"))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    ft.as_account()
+        .call(ft.id(), "ft_transfer")
+        .args_json(json!({"receiver_id": alice.id(), "amount": "500"}))
+        .deposit(1)
+        .transact()
+        .await?
+        .into_result()?;
+
+    // 將代幣加入白名單，bob發一篇文章
+    contract
+        .as_account()
+        .call(contract.id(), "add_ft_token")
+        .args_json(json!({"token_id": ft.id()}))
+        .transact()
+        .await?
+        .into_result()?;
+    bob.call(contract.id(), "add_post")
+        .args_json(json!({
+            "title": "Hello",
+            "content": "Tip me",
+            "tags": [],
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // alice打賞bob的文章
+    alice
+        .call(ft.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": contract.id(),
+            "amount": "100",
+            "msg": json!({"Post": {"post_id": 0}}).to_string(),
+        }))
+        .deposit(1)
+        .gas(300_000_000_000_000)
+        .transact()
+        .await?
+        .into_result()?;
+    let tipped: String = alice
+        .call(contract.id(), "get_ft_balance")
+        .args_json(json!({"account_id": bob.id(), "token_id": ft.id()}))
         .transact()
         .await?
         .json()?;
+    assert_eq!(tipped, "100".to_string());
+    assert_eq!(ft_balance_of(alice, ft, alice.id()).await?, "400".to_string());
 
-    assert_eq!(message, "Hello".to_string());
-    println!("      Passed ✅ gets default message");
+    // 不合法的msg會全數退還
+    alice
+        .call(ft.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": contract.id(),
+            "amount": "50",
+            "msg": "not a post",
+        }))
+        .deposit(1)
+        .gas(300_000_000_000_000)
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(ft_balance_of(alice, ft, alice.id()).await?, "400".to_string());
+
+    // bob提領收到的代幣
+    bob.call(contract.id(), "withdraw_ft")
+        .args_json(json!({"token_id": ft.id(), "amount": null}))
+        .deposit(1)
+        .gas(300_000_000_000_000)
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(ft_balance_of(bob, ft, bob.id()).await?, "100".to_string());
+    println!("      Passed ✅ tips authors with fungible tokens");
     Ok(())
 }
 
-async fn test_changes_message(
+// 作者的金鑰伺服器：確認帳號買過文章才發放解密金鑰
+async fn key_server_release_key(
     user: &Account,
     contract: &Contract,
+    post_id: u128,
+    account_id: &AccountId,
+) -> anyhow::Result<Option<String>> {
+    let purchased: bool = user
+        .call(contract.id(), "has_purchased")
+        .args_json(json!({"post_id": post_id, "account_id": account_id}))
+        .transact()
+        .await?
+        .json()?;
+    Ok(if purchased {
+        Some("secret-key".to_string())
+    } else {
+        None
+    })
+}
+
+async fn test_premium_posts(
+    alice: &Account,
+    bob: &Account,
+    contract: &Contract,
 ) -> anyhow::Result<()> {
-    user.call(contract.id(), "set_greeting")
-        .args_json(json!({"message": "Howdy"}))
+    // bob發一篇付費文章（內文是加密過的）
+    let post: serde_json::Value = bob
+        .call(contract.id(), "add_post")
+        .args_json(json!({
+            "title": "Premium",
+            "content": "ZW5jcnlwdGVkIHdvcmRz",
+            "tags": [],
+            "options": { "premium_price": parse_near!("1 N").to_string() },
+        }))
         .transact()
         .await?
-        .into_result()?;
+        .json()?;
+    let post_id: u128 = serde_json::from_value(post["id"].clone())?;
 
-    let message: String = user
-        .call(contract.id(), "get_greeting")
-        .args_json(json!({}))
+    // 加密過的內文不會被搜尋到
+    let found: Vec<serde_json::Value> = alice
+        .call(contract.id(), "search_post")
+        .args_json(json!({"q": "ZW5jcnlwdGVk"}))
         .transact()
         .await?
         .json()?;
+    assert!(found.is_empty());
 
-    assert_eq!(message, "Howdy".to_string());
-    println!("      Passed ✅ changes message");
+    // 還沒購買拿不到金鑰
+    assert_eq!(
+        key_server_release_key(alice, contract, post_id, alice.id()).await?,
+        None
+    );
+    alice
+        .call(contract.id(), "purchase_post")
+        .args_json(json!({"post_id": post_id}))
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        key_server_release_key(alice, contract, post_id, alice.id()).await?,
+        Some("secret-key".to_string())
+    );
+
+    // bob提領收入
+    let revenue: String = bob
+        .call(contract.id(), "get_premium_revenue")
+        .args_json(json!({"account_id": bob.id()}))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(revenue, parse_near!("1 N").to_string());
+    let balance_before = bob.view_account().await?.balance;
+    bob.call(contract.id(), "withdraw_revenue")
+        .args_json(json!({}))
+        .deposit(1)
+        .gas(300_000_000_000_000)
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(bob.view_account().await?.balance > balance_before);
+    println!("      Passed ✅ sells premium posts");
     Ok(())
-}
\ No newline at end of file
+}